@@ -0,0 +1,144 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use duckdb::types::ValueRef;
+use duckdb::{params, Connection};
+
+use crate::error::AppError;
+use crate::models::BlobData;
+use crate::quote::{qualified_name, quote_identifier};
+
+/// Number of bytes shown in `BlobData::hex_preview`
+const HEX_PREVIEW_BYTES: usize = 512;
+
+/// Read the BLOB stored in `column` of the row of `table_name` with the given
+/// `rowid`. The table is looked up in `database` and `schema` when given,
+/// otherwise in the session's defaults. VARCHAR cells are returned as their
+/// UTF-8 bytes.
+pub fn read_cell_blob(
+    conn: &Connection,
+    table_name: &str,
+    database: Option<&str>,
+    schema: Option<&str>,
+    rowid: i64,
+    column: &str,
+) -> Result<Vec<u8>, AppError> {
+    let sql = format!(
+        "SELECT {} FROM {} WHERE rowid = ?",
+        quote_identifier(column),
+        qualified_name(database, schema, table_name)
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query(params![rowid])?;
+    let row = rows
+        .next()?
+        .ok_or_else(|| AppError::invalid_input(format!("Row {} not found in {}", rowid, table_name)))?;
+
+    match row.get_ref(0)? {
+        ValueRef::Blob(bytes) | ValueRef::Text(bytes) => Ok(bytes.to_vec()),
+        ValueRef::Null => Err(AppError::invalid_input(format!("{} is NULL in row {}", column, rowid))),
+        _ => Err(AppError::invalid_input(format!("{} is not a BLOB column", column))),
+    }
+}
+
+/// Package BLOB bytes for display: base64 content, hex preview and MIME type
+pub fn blob_data(bytes: &[u8]) -> BlobData {
+    BlobData {
+        size: bytes.len(),
+        mime_type: sniff_mime_type(bytes).to_string(),
+        base64: STANDARD.encode(bytes),
+        hex_preview: hex_dump(&bytes[..bytes.len().min(HEX_PREVIEW_BYTES)]),
+    }
+}
+
+/// Guess the MIME type from magic numbers, falling back to text/plain for
+/// valid UTF-8
+fn sniff_mime_type(bytes: &[u8]) -> &'static str {
+    if let Some(kind) = infer::get(bytes) {
+        kind.mime_type()
+    } else if !bytes.is_empty() && std::str::from_utf8(bytes).is_ok() {
+        "text/plain"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+/// Offset, 16 hex bytes and their printable ASCII per line, like `hexdump -C`
+fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(line, chunk)| {
+            let hex = chunk
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect();
+            format!("{:08x}  {:<47}  |{}|", line * 16, hex, ascii)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_HEADER: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    fn blob_connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE files (name VARCHAR, data BLOB)")
+            .unwrap();
+        conn.execute(
+            "INSERT INTO files VALUES ('image', ?), ('empty', NULL)",
+            params![PNG_HEADER],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_read_cell_blob() {
+        let conn = blob_connection();
+
+        assert_eq!(read_cell_blob(&conn, "files", None, None, 0, "data").unwrap(), PNG_HEADER);
+        assert_eq!(read_cell_blob(&conn, "files", None, None, 0, "name").unwrap(), b"image");
+        assert!(read_cell_blob(&conn, "files", None, None, 1, "data").is_err());
+        assert!(read_cell_blob(&conn, "files", None, None, 2, "data").is_err());
+
+        conn.execute_batch("CREATE SCHEMA other; CREATE TABLE other.files AS SELECT 'zz'::BLOB AS data")
+            .unwrap();
+        assert_eq!(read_cell_blob(&conn, "files", Some("memory"), Some("other"), 0, "data").unwrap(), b"zz");
+    }
+
+    #[test]
+    fn test_blob_data() {
+        let data = blob_data(PNG_HEADER);
+        assert_eq!(data.size, 16);
+        assert_eq!(data.mime_type, "image/png");
+        assert_eq!(data.base64, "iVBORw0KGgoAAAANSUhEUg==");
+        assert_eq!(
+            data.hex_preview,
+            "00000000  89 50 4e 47 0d 0a 1a 0a 00 00 00 0d 49 48 44 52  |.PNG........IHDR|"
+        );
+
+        assert_eq!(blob_data(b"hello").mime_type, "text/plain");
+        assert_eq!(blob_data(&[0xff, 0xfe, 0x00]).mime_type, "application/octet-stream");
+    }
+
+    #[test]
+    fn test_hex_dump_partial_line() {
+        let dump = hex_dump(b"0123456789abcdefXY");
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[1],
+            "00000010  58 59                                            |XY|"
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use duckdb::Connection;
+
+use crate::db::TEMP_CATALOG;
+use crate::error::AppError;
+use crate::file_views::is_file_view;
+use crate::models::{CatalogDatabase, CatalogObject, CatalogObjectType, CatalogSchema};
+
+/// Every database of the session with its schemas and the tables, views,
+/// sequences, macros and indexes in them. The default database comes first
+/// and `temp`, holding temporary objects, last when it has any.
+///
+/// DuckDB does not track object owners, so none are reported.
+pub fn get_catalog(conn: &Connection) -> Result<Vec<CatalogDatabase>, AppError> {
+    let mut databases: Vec<CatalogDatabase> = Vec::new();
+    let mut schema_index: HashMap<(String, String), (usize, usize)> = HashMap::new();
+
+    let mut stmt = conn.prepare(
+        "SELECT s.database_name, d.path, s.database_name = current_database() AS is_default,
+                s.schema_name, s.comment
+         FROM duckdb_schemas() s
+         JOIN duckdb_databases() d USING (database_name)
+         WHERE s.database_name <> 'system' AND (NOT s.internal OR s.schema_name = 'main')
+         ORDER BY is_default DESC, s.database_name = 'temp', s.database_name,
+                  s.schema_name <> 'main', s.schema_name",
+    )?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let database: String = row.get(0)?;
+        let schema: String = row.get(3)?;
+
+        if databases.last().map(|last| &last.name) != Some(&database) {
+            databases.push(CatalogDatabase {
+                name: database.clone(),
+                path: row.get(1)?,
+                is_default: row.get(2)?,
+                schemas: Vec::new(),
+            });
+        }
+        let database_position = databases.len() - 1;
+        let schemas = &mut databases[database_position].schemas;
+        schema_index.insert((database, schema.clone()), (database_position, schemas.len()));
+        schemas.push(CatalogSchema {
+            name: schema,
+            comment: row.get(4)?,
+            objects: Vec::new(),
+        });
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT database_name, schema_name, name, object_type, comment, table_name
+         FROM (
+             SELECT database_name, schema_name, table_name AS name, 'table' AS object_type, comment,
+                    NULL AS table_name, 1 AS rank
+             FROM duckdb_tables() WHERE NOT internal
+             UNION ALL
+             SELECT database_name, schema_name, view_name, 'view', comment, NULL, 2
+             FROM duckdb_views() WHERE NOT internal
+             UNION ALL
+             SELECT database_name, schema_name, sequence_name, 'sequence', comment, NULL, 3
+             FROM duckdb_sequences()
+             UNION ALL
+             SELECT DISTINCT database_name, schema_name, function_name, function_type, comment, NULL, 4
+             FROM duckdb_functions()
+             WHERE NOT internal AND function_type IN ('macro', 'table_macro')
+             UNION ALL
+             SELECT database_name, schema_name, index_name, 'index', comment, table_name, 5
+             FROM duckdb_indexes()
+         )
+         ORDER BY rank, name",
+    )?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let database: String = row.get(0)?;
+        let schema: String = row.get(1)?;
+        let Some(&(database_position, schema_position)) = schema_index.get(&(database.clone(), schema)) else {
+            continue;
+        };
+
+        let object_type = match row.get::<_, String>(3)?.as_str() {
+            "table" => CatalogObjectType::Table,
+            "view" if is_file_view(row.get::<_, Option<String>>(4)?.as_deref()) => CatalogObjectType::FileView,
+            "view" => CatalogObjectType::View,
+            "sequence" => CatalogObjectType::Sequence,
+            "macro" => CatalogObjectType::Macro,
+            "table_macro" => CatalogObjectType::TableMacro,
+            _ => CatalogObjectType::Index,
+        };
+
+        databases[database_position].schemas[schema_position]
+            .objects
+            .push(CatalogObject {
+                name: row.get(2)?,
+                object_type,
+                comment: row.get(4)?,
+                temporary: database == TEMP_CATALOG,
+                table_name: row.get(5)?,
+            });
+    }
+
+    databases.retain(|database| {
+        database.name != TEMP_CATALOG || database.schemas.iter().any(|schema| !schema.objects.is_empty())
+    });
+
+    Ok(databases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Database, schema and the (name, type) of each object in it
+    type SchemaSummary = (String, String, Vec<(String, CatalogObjectType)>);
+
+    #[test]
+    fn test_get_catalog() {
+        let conn = Connection::open_in_memory().unwrap();
+        let empty = get_catalog(&conn).unwrap();
+        assert_eq!(empty.len(), 1);
+        assert_eq!(empty[0].name, "memory");
+        assert_eq!(empty[0].schemas[0].name, "main");
+
+        conn.execute_batch(
+            "CREATE SCHEMA sales;
+             CREATE TABLE sales.orders (id INTEGER, amount DOUBLE);
+             COMMENT ON TABLE sales.orders IS 'One row per order';
+             CREATE INDEX orders_id ON sales.orders (id);
+             CREATE VIEW big_orders AS SELECT * FROM sales.orders WHERE amount > 100;
+             CREATE SEQUENCE order_ids;
+             CREATE MACRO add_tax(x) AS x * 1.1;
+             CREATE MACRO recent() AS TABLE SELECT * FROM sales.orders;
+             CREATE TEMP TABLE scratch (i INTEGER);",
+        )
+        .unwrap();
+
+        let catalog = get_catalog(&conn).unwrap();
+        let summary: Vec<SchemaSummary> = catalog
+            .iter()
+            .flat_map(|database| {
+                database.schemas.iter().map(|schema| {
+                    (
+                        database.name.clone(),
+                        schema.name.clone(),
+                        schema
+                            .objects
+                            .iter()
+                            .map(|object| (object.name.clone(), object.object_type))
+                            .collect(),
+                    )
+                })
+            })
+            .collect();
+
+        let object = |name: &str, object_type| (name.to_string(), object_type);
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "memory".to_string(),
+                    "main".to_string(),
+                    vec![
+                        object("big_orders", CatalogObjectType::View),
+                        object("order_ids", CatalogObjectType::Sequence),
+                        object("add_tax", CatalogObjectType::Macro),
+                        object("recent", CatalogObjectType::TableMacro),
+                    ]
+                ),
+                (
+                    "memory".to_string(),
+                    "sales".to_string(),
+                    vec![
+                        object("orders", CatalogObjectType::Table),
+                        object("orders_id", CatalogObjectType::Index),
+                    ]
+                ),
+                (
+                    "temp".to_string(),
+                    "main".to_string(),
+                    vec![object("scratch", CatalogObjectType::Table)]
+                ),
+            ]
+        );
+
+        let orders = &catalog[0].schemas[1].objects[0];
+        assert_eq!(orders.comment.as_deref(), Some("One row per order"));
+        assert!(!orders.temporary);
+        assert_eq!(catalog[0].schemas[1].objects[1].table_name.as_deref(), Some("orders"));
+        assert!(catalog[1].schemas[0].objects[0].temporary);
+    }
+}
@@ -1,28 +1,100 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
-use tauri::State;
+use duckdb::arrow::record_batch::RecordBatch;
+use duckdb::Connection;
+use tauri::{AppHandle, State};
 use tokio::time::timeout;
-
-use crate::db::DatabaseManager;
+use uuid::Uuid;
+
+use crate::blob::{blob_data, read_cell_blob};
+use crate::catalog;
+use crate::databases;
+use crate::ddl;
+use crate::db::{DatabaseHandle, DatabaseManager, IN_MEMORY_CATALOG, IN_MEMORY_PATH};
+use crate::error::AppError;
 use crate::export::{export_to_csv, export_to_excel, export_to_json, export_to_parquet_via_duckdb};
-use crate::models::{ColumnInfo, DatabaseInfo, ExportFormat, QueryResult, TableInfo, TableSchema};
+use crate::file_views;
+use crate::filter::{filter_sql, order_by_sql};
+use crate::models::{
+    AccessMode, AttachedDatabase, BlobData, CatalogDatabase, CurrentDatabase, CursorInfo, DatabaseInfo, ExportFormat, FileViewFormat, ObjectRef, ProfileOptions,
+    QueryResult, ResultEncoding, RowBatch, ScriptResult, SourceType, TableDataOptions, TableInfo, TablePage, TableProfile, TableSchema,
+};
+use crate::params::{bind_params, QueryParams};
+use crate::profile;
+use crate::quote::{qualified_name, quote_literal, quote_path};
+use crate::script;
+use crate::settings::{OperationKind, Settings, SettingsManager};
+use crate::table_schema;
+use crate::tables;
+use crate::types::column_types;
+use crate::values::{chunk_row_to_json, ValueFormat};
+
+/// Helper to run blocking database operations, with a timeout unless `time_limit` is `None`
+async fn run_blocking<F, T>(time_limit: Option<Duration>, f: F) -> Result<T, AppError>
+where
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+    T: Send + 'static,
+{
+    let task = tokio::task::spawn_blocking(f);
 
-/// Default timeout for database operations (30 seconds)
-const DB_TIMEOUT: Duration = Duration::from_secs(30);
+    let result = match time_limit {
+        Some(time_limit) => timeout(time_limit, task)
+            .await
+            .map_err(|_| AppError::Timeout)?,
+        None => task.await,
+    }?;
 
-/// Helper to run blocking database operations with timeout
-async fn run_blocking<F, T>(f: F) -> Result<T, String>
+    result
+}
+
+/// Helper to run work on the cursor `cursor_id` with a timeout unless
+/// `time_limit` is `None`. On timeout the cursor is closed, as the rows the
+/// abandoned work reads are lost to the caller.
+async fn run_cursor_blocking<F, T>(
+    handle: Arc<DatabaseHandle>,
+    cursor_id: String,
+    time_limit: Option<Duration>,
+    f: F,
+) -> Result<T, AppError>
 where
-    F: FnOnce() -> Result<T, String> + Send + 'static,
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
     T: Send + 'static,
 {
-    let result = timeout(
-        DB_TIMEOUT,
-        tokio::task::spawn_blocking(f)
-    )
-    .await
-    .map_err(|_| "Operation timed out".to_string())?
-    .map_err(|e| format!("Task failed: {}", e))?;
+    let result = run_blocking(time_limit, f).await;
+    if matches!(result, Err(AppError::Timeout)) {
+        handle.close_cursor(&cursor_id);
+    }
+    result
+}
+
+/// Helper to run cancellable work on the session connection, with a timeout
+/// unless `time_limit` is `None`. On timeout the statement is interrupted rather
+/// than left running while holding the connection.
+async fn run_query_blocking<F, T>(
+    handle: Arc<DatabaseHandle>,
+    query_id: Option<String>,
+    time_limit: Option<Duration>,
+    f: F,
+) -> Result<T, AppError>
+where
+    F: FnOnce(&Connection) -> Result<T, AppError> + Send + 'static,
+    T: Send + 'static,
+{
+    let query_id = query_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    let query = handle.register_query(query_id.clone());
+    let task = tokio::task::spawn_blocking(move || query.run(f));
+
+    let result = match time_limit {
+        Some(time_limit) => match timeout(time_limit, task).await {
+            Ok(result) => result,
+            Err(_) => {
+                handle.cancel_query(&query_id);
+                return Err(AppError::Timeout);
+            }
+        },
+        None => task.await,
+    }?;
 
     result
 }
@@ -31,222 +103,554 @@ where
 // Database Commands
 // ============================================================================
 
+/// Open an existing database file, read-write unless `access_mode` says
+/// otherwise. In a read-only session statements that would modify the file
+/// fail with [`AppError::ReadOnly`]. Opening fails while another process has
+/// the file open read-write, or, for a read-write open, open at all.
 #[tauri::command]
 pub async fn open_database(
     path: String,
+    access_mode: Option<AccessMode>,
     db: State<'_, DatabaseManager>,
-) -> Result<DatabaseInfo, String> {
+    settings: State<'_, SettingsManager>,
+) -> Result<DatabaseInfo, AppError> {
     let path_clone = path.clone();
+    let access_mode = access_mode.unwrap_or_default();
 
-    let result = run_blocking(move || {
-        let conn = duckdb::Connection::open(&path_clone)
-            .map_err(|e| format!("Failed to open database: {}", e))?;
-
-        let tables = get_table_names(&conn)?;
+    // The open database stays open if this one fails to open, unless it is
+    // the same file, whose lock it must release first
+    if db.is_open(Path::new(&path)) {
+        db.close();
+    }
 
-        Ok(DatabaseInfo {
-            path: path_clone,
-            tables,
-        })
+    let (handle, tables) = run_blocking(settings.timeout(OperationKind::Metadata), move || {
+        let handle = DatabaseHandle::open(PathBuf::from(&path_clone), access_mode)?;
+        let tables = get_table_names(&*handle.connection()?)?;
+        Ok((handle, tables))
     })
     .await?;
 
-    db.set_path(Some(PathBuf::from(&path)));
-    Ok(result)
+    db.set(handle);
+    Ok(DatabaseInfo {
+        path,
+        tables,
+        access_mode,
+    })
 }
 
 #[tauri::command]
 pub async fn create_database(
     path: String,
     db: State<'_, DatabaseManager>,
-) -> Result<DatabaseInfo, String> {
+    settings: State<'_, SettingsManager>,
+) -> Result<DatabaseInfo, AppError> {
     let path_clone = path.clone();
 
-    run_blocking(move || {
-        let _conn = duckdb::Connection::open(&path_clone)
-            .map_err(|e| format!("Failed to create database: {}", e))?;
-        Ok(())
+    if db.is_open(Path::new(&path)) {
+        db.close();
+    }
+
+    let handle = run_blocking(settings.timeout(OperationKind::Metadata), move || {
+        DatabaseHandle::open(PathBuf::from(&path_clone), AccessMode::ReadWrite)
     })
     .await?;
 
-    db.set_path(Some(PathBuf::from(&path)));
+    db.set(handle);
 
     Ok(DatabaseInfo {
         path,
         tables: vec![],
+        access_mode: AccessMode::ReadWrite,
+    })
+}
+
+/// Open a scratch database that lives in memory, for ad-hoc analysis of
+/// imported files. Nothing is written to disk unless `persist_database` is
+/// called before closing it.
+#[tauri::command]
+pub async fn open_memory_database(
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<DatabaseInfo, AppError> {
+    let handle = run_blocking(settings.timeout(OperationKind::Metadata), DatabaseHandle::open_in_memory).await?;
+
+    db.set(handle);
+
+    Ok(DatabaseInfo {
+        path: IN_MEMORY_PATH.to_string(),
+        tables: vec![],
+        access_mode: AccessMode::ReadWrite,
+    })
+}
+
+/// Write the whole in-memory database (schemas, tables, views, sequences and
+/// macros) to a new DuckDB file at `path`. The session stays in memory.
+#[tauri::command]
+pub async fn persist_database(
+    path: String,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<(), AppError> {
+    let handle = db.get()?;
+    if !handle.is_in_memory() {
+        return Err(AppError::invalid_input("Only an in-memory database can be persisted"));
+    }
+    let time_limit = settings.timeout(OperationKind::Export);
+
+    run_query_blocking(handle, None, time_limit, move |conn| {
+        databases::persist_database(conn, IN_MEMORY_CATALOG, &path)
     })
+    .await
 }
 
 #[tauri::command]
-pub async fn close_database(db: State<'_, DatabaseManager>) -> Result<(), String> {
-    db.set_path(None);
+pub async fn close_database(db: State<'_, DatabaseManager>) -> Result<(), AppError> {
+    db.close();
     Ok(())
 }
 
 #[tauri::command]
-pub async fn get_current_database(db: State<'_, DatabaseManager>) -> Result<Option<String>, String> {
-    Ok(db.get_path().map(|p| p.to_string_lossy().to_string()))
+pub async fn get_current_database(db: State<'_, DatabaseManager>) -> Result<Option<CurrentDatabase>, AppError> {
+    Ok(db.current())
 }
 
 // ============================================================================
-// Table Commands
+// Attached Database Commands
 // ============================================================================
 
+/// The session's databases: the opened file and every attached one
+#[tauri::command]
+pub async fn list_databases(
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<Vec<AttachedDatabase>, AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Metadata);
+
+    run_query_blocking(handle, None, time_limit, databases::list_databases).await
+}
+
+/// ATTACH another database file to the session as `alias`. It is attached
+/// with the session's access mode unless `access_mode` says otherwise; a
+/// read-only session can only attach read-only.
 #[tauri::command]
-pub async fn get_tables(db: State<'_, DatabaseManager>) -> Result<Vec<TableInfo>, String> {
-    let path = db.get_path().ok_or("No database selected")?;
+pub async fn attach_database(
+    path: String,
+    alias: Option<String>,
+    access_mode: Option<AccessMode>,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<AttachedDatabase, AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Metadata);
+    let access_mode = access_mode.unwrap_or(handle.access_mode());
+
+    run_query_blocking(handle, None, time_limit, move |conn| {
+        databases::attach_database(conn, &path, alias.as_deref(), access_mode)
+    })
+    .await
+}
+
+/// ATTACH an external source (SQLite file, Postgres or MySQL server, ...)
+/// under `alias` so its tables can be listed, queried and exported like
+/// native ones. See [`SourceType`] for what `location` holds.
+#[tauri::command]
+pub async fn attach_source(
+    source_type: SourceType,
+    location: String,
+    alias: Option<String>,
+    access_mode: Option<AccessMode>,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<AttachedDatabase, AppError> {
+    let handle = db.get()?;
+    // Attaching installs the source's scanner extension first, which is a
+    // download on first use, so it gets the import timeout
+    let time_limit = settings.timeout(OperationKind::Import);
+    let access_mode = access_mode.unwrap_or(handle.access_mode());
+
+    run_query_blocking(handle, None, time_limit, move |conn| {
+        databases::attach_source(conn, source_type, &location, alias.as_deref(), access_mode)
+    })
+    .await
+}
 
-    run_blocking(move || {
-        let conn = duckdb::Connection::open(&path)
-            .map_err(|e| format!("Failed to open database: {}", e))?;
+#[tauri::command]
+pub async fn detach_database(
+    name: String,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<(), AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Metadata);
 
-        let table_names = get_table_names(&conn)?;
+    run_query_blocking(handle, None, time_limit, move |conn| databases::detach_database(conn, &name)).await
+}
 
-        let mut tables = Vec::new();
-        for name in table_names {
-            let count_sql = format!("SELECT COUNT(*) FROM \"{}\"", name);
-            let row_count: i64 = conn
-                .query_row(&count_sql, [], |row| row.get(0))
-                .unwrap_or(0);
-            tables.push(TableInfo { name, row_count });
-        }
+/// Switch the default database (and optionally schema) that unqualified
+/// table names resolve against
+#[tauri::command]
+pub async fn use_database(
+    name: String,
+    schema: Option<String>,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<AttachedDatabase, AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Metadata);
+
+    run_query_blocking(handle, None, time_limit, move |conn| {
+        databases::use_database(conn, &name, schema.as_deref())
+    })
+    .await
+}
+
+// ============================================================================
+// Table Commands
+// ============================================================================
 
-        Ok(tables)
+/// Tables and views of every database in the session with their database
+/// and schema, those of the default database first. Row counts are DuckDB's
+/// estimates unless `exact_counts` is set, in which case every table is
+/// counted, several at a time. Storage sizes are left out unless
+/// `storage_sizes` is set.
+#[tauri::command]
+pub async fn get_tables(
+    exact_counts: Option<bool>,
+    storage_sizes: Option<bool>,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<Vec<TableInfo>, AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Metadata);
+    let session = Arc::clone(&handle);
+
+    run_query_blocking(handle, None, time_limit, move |conn| {
+        let interrupts = session.interrupt_group();
+        tables::list_tables(conn, exact_counts.unwrap_or(false), storage_sizes.unwrap_or(false), &interrupts)
     })
     .await
 }
 
+/// The session's catalog as a tree of databases, schemas and the tables,
+/// views, sequences, macros and indexes in them
+#[tauri::command]
+pub async fn get_catalog(
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<Vec<CatalogDatabase>, AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Metadata);
+
+    run_query_blocking(handle, None, time_limit, catalog::get_catalog).await
+}
+
+/// Columns, constraints, comments and indexes of `table_name`, looked up in
+/// `database` and `schema` when given and in the session's defaults otherwise
 #[tauri::command]
 pub async fn get_table_schema(
     table_name: String,
+    database: Option<String>,
+    schema: Option<String>,
     db: State<'_, DatabaseManager>,
-) -> Result<TableSchema, String> {
-    let path = db.get_path().ok_or("No database selected")?;
-
-    run_blocking(move || {
-        let conn = duckdb::Connection::open(&path)
-            .map_err(|e| format!("Failed to open database: {}", e))?;
-
-        let sql = format!("PRAGMA table_info('{}')", table_name);
-        let mut stmt = conn.prepare(&sql)
-            .map_err(|e| format!("Failed to prepare query: {}", e))?;
-
-        let columns: Vec<ColumnInfo> = stmt
-            .query_map([], |row| {
-                Ok(ColumnInfo {
-                    name: row.get(1)?,
-                    data_type: row.get(2)?,
-                    nullable: !row.get::<_, bool>(3)?,
-                    default_value: row.get(4).ok(),
-                    is_primary_key: row.get::<_, bool>(5).unwrap_or(false),
-                })
-            })
-            .map_err(|e| format!("Failed to execute query: {}", e))?
-            .filter_map(|r| r.ok())
-            .collect();
+    settings: State<'_, SettingsManager>,
+) -> Result<TableSchema, AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Metadata);
+
+    run_query_blocking(handle, None, time_limit, move |conn| {
+        table_schema::get_table_schema(conn, &table_name, database.as_deref(), schema.as_deref())
+    })
+    .await
+}
+
+/// Per-column statistics (nulls, distinct values, min/max, mean, standard
+/// deviation, quantiles, most frequent values and a histogram) of
+/// `table_name`, or of the result of `query` when that is given instead.
+/// See [`ProfileOptions`] for limiting the columns and sampling rows.
+#[tauri::command]
+pub async fn profile_table(
+    table_name: Option<String>,
+    database: Option<String>,
+    schema: Option<String>,
+    query: Option<String>,
+    options: Option<ProfileOptions>,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<TableProfile, AppError> {
+    let (relation, materialize) = match (table_name, query) {
+        (Some(table_name), None) => (qualified_name(database.as_deref(), schema.as_deref(), &table_name), false),
+        (None, Some(query)) => (format!("({})", query.trim().trim_end_matches(';')), true),
+        _ => return Err(AppError::invalid_input("Give either a table name or a query to profile")),
+    };
+    let options = options.unwrap_or_default();
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Query);
+
+    run_query_blocking(handle, None, time_limit, move |conn| {
+        profile::profile(conn, &relation, options.columns.as_deref(), options.sample, materialize)
+    })
+    .await
+}
+
+/// SQL recreating `object` and its comments, with the indexes of a table
+#[tauri::command]
+pub async fn get_ddl(
+    object: ObjectRef,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<String, AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Metadata);
+
+    run_query_blocking(handle, None, time_limit, move |conn| ddl::get_ddl(conn, &object)).await
+}
+
+/// A dependency-ordered script recreating every schema, table, view,
+/// sequence, macro and index of `database`, the default database if not given
+#[tauri::command]
+pub async fn dump_schema(
+    database: Option<String>,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<String, AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Metadata);
 
-        Ok(TableSchema { table_name, columns })
+    run_query_blocking(handle, None, time_limit, move |conn| {
+        ddl::dump_schema(conn, database.as_deref())
     })
     .await
 }
 
+/// A page of `table_name` (looked up in `database` and `schema` when given,
+/// otherwise in the session's defaults), filtered and sorted as `options` says, with the
+/// number of rows passing the filter. Filter values are bound as parameters.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn get_table_data(
     table_name: String,
+    database: Option<String>,
+    schema: Option<String>,
     limit: usize,
     offset: usize,
+    options: Option<TableDataOptions>,
     db: State<'_, DatabaseManager>,
-) -> Result<QueryResult, String> {
-    let path = db.get_path().ok_or("No database selected")?;
-    let sql = format!(
-        "SELECT * FROM \"{}\" LIMIT {} OFFSET {}",
-        table_name, limit, offset
-    );
+    settings: State<'_, SettingsManager>,
+) -> Result<TablePage, AppError> {
+    let options = options.unwrap_or_default();
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Query);
+    let value_format = ValueFormat::new(&settings.display(), ResultEncoding::Plain);
+
+    run_query_blocking(handle, None, time_limit, move |conn| {
+        let with_rowid = is_base_table(conn, &table_name, database.as_deref(), schema.as_deref())?;
+        let table = qualified_name(database.as_deref(), schema.as_deref(), &table_name);
+        table_page(conn, &table, with_rowid, &options, limit, offset, &value_format)
+    })
+    .await
+}
+
+// ============================================================================
+// Blob Commands
+// ============================================================================
+
+/// Read a BLOB cell for viewing. `rowid` is DuckDB's `rowid` pseudo-column,
+/// as in the `rowids` of a `get_table_data` page.
+#[tauri::command]
+pub async fn get_cell_blob(
+    table_name: String,
+    database: Option<String>,
+    schema: Option<String>,
+    rowid: i64,
+    column: String,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<BlobData, AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Query);
+
+    run_query_blocking(handle, None, time_limit, move |conn| {
+        read_cell_blob(conn, &table_name, database.as_deref(), schema.as_deref(), rowid, &column).map(|bytes| blob_data(&bytes))
+    })
+    .await
+}
 
-    run_blocking(move || execute_query_sync(&path, &sql)).await
+/// Write the raw bytes of a BLOB cell to `file_path`
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn save_blob(
+    table_name: String,
+    database: Option<String>,
+    schema: Option<String>,
+    rowid: i64,
+    column: String,
+    file_path: String,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<(), AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Export);
+
+    run_query_blocking(handle, None, time_limit, move |conn| {
+        let bytes = read_cell_blob(conn, &table_name, database.as_deref(), schema.as_deref(), rowid, &column)?;
+        Ok(std::fs::write(&file_path, bytes)?)
+    })
+    .await
 }
 
 // ============================================================================
 // Query Commands
 // ============================================================================
 
+/// Execute `sql`, binding `params` to its placeholders. The frontend may pass
+/// its own `query_id` so the query can be stopped with `cancel_query` while it
+/// runs, `timeout_ms` to override the configured query timeout, and
+/// `encoding` to choose how numbers are returned (see [`ResultEncoding`]).
 #[tauri::command]
 pub async fn execute_query(
     sql: String,
+    params: Option<QueryParams>,
+    query_id: Option<String>,
+    timeout_ms: Option<u64>,
+    encoding: Option<ResultEncoding>,
     db: State<'_, DatabaseManager>,
-) -> Result<QueryResult, String> {
-    let path = db.get_path().ok_or("No database selected")?;
+    settings: State<'_, SettingsManager>,
+) -> Result<QueryResult, AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.query_timeout(timeout_ms)?;
+    let value_format = ValueFormat::new(&settings.display(), encoding.unwrap_or_default());
+
+    run_query_blocking(handle, query_id, time_limit, move |conn| {
+        execute_query_with_params(conn, &sql, &params.unwrap_or_default(), &value_format)
+    })
+    .await
+}
 
-    run_blocking(move || execute_query_sync(&path, &sql)).await
+/// Run a multi-statement script, one statement after another. Script errors
+/// are reported in the result; only timeouts and cancellation fail the call.
+#[tauri::command]
+pub async fn execute_script(
+    script: String,
+    query_id: Option<String>,
+    timeout_ms: Option<u64>,
+    encoding: Option<ResultEncoding>,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<ScriptResult, AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.query_timeout(timeout_ms)?;
+    let value_format = ValueFormat::new(&settings.display(), encoding.unwrap_or_default());
+
+    run_query_blocking(handle, query_id, time_limit, move |conn| {
+        Ok(script::execute_script(conn, &script, &value_format))
+    })
+    .await
+}
+
+/// Cancel an in-flight `execute_query` or `execute_script`; it then fails
+/// with [`AppError::Cancelled`]. Returns false if the query already finished or was never started.
+#[tauri::command]
+pub async fn cancel_query(
+    query_id: String,
+    db: State<'_, DatabaseManager>,
+) -> Result<bool, AppError> {
+    Ok(db.get()?.cancel_query(&query_id))
+}
+
+/// Execute `sql` as a server-side cursor instead of returning every row.
+/// Read it with `fetch_rows` and release it with `close_cursor`. The query
+/// runs on the session connection, so it sees the session's temporary tables
+/// and SET variables, and `cancel_query` or `close_cursor` with the cursor's
+/// id stops it while it is opening. Idle cursors are closed after a while.
+#[tauri::command]
+pub async fn open_query(
+    sql: String,
+    encoding: Option<ResultEncoding>,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<CursorInfo, AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Query);
+    let value_format = ValueFormat::new(&settings.display(), encoding.unwrap_or_default());
+    let cursor_id = handle.reserve_cursor()?;
+
+    let opener = Arc::clone(&handle);
+    let id = cursor_id.clone();
+    let result = run_query_blocking(Arc::clone(&handle), Some(cursor_id.clone()), time_limit, move |conn| {
+        opener.open_cursor(conn, id, &sql, value_format)
+    })
+    .await;
+
+    // A cursor that timed out or was cancelled must not stay open under an
+    // id the caller never learns
+    if result.is_err() {
+        handle.close_cursor(&cursor_id);
+    }
+    result
+}
+
+/// Fetch up to `max_rows` further rows from a cursor
+#[tauri::command]
+pub async fn fetch_rows(
+    cursor_id: String,
+    max_rows: usize,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<RowBatch, AppError> {
+    let handle = db.get()?;
+    let cursor = handle.cursor(&cursor_id)?;
+    let time_limit = settings.timeout(OperationKind::Query);
+
+    run_cursor_blocking(handle, cursor_id, time_limit, move || Ok(cursor.fetch(max_rows))).await
+}
+
+#[tauri::command]
+pub async fn close_cursor(
+    cursor_id: String,
+    db: State<'_, DatabaseManager>,
+) -> Result<bool, AppError> {
+    Ok(db.get()?.close_cursor(&cursor_id))
 }
 
 /// Synchronous query execution (runs in blocking thread)
-fn execute_query_sync(path: &std::path::Path, sql: &str) -> Result<QueryResult, String> {
+pub(crate) fn execute_query_sync(conn: &Connection, sql: &str, value_format: &ValueFormat) -> Result<QueryResult, AppError> {
+    execute_query_with_params(conn, sql, &QueryParams::default(), value_format)
+}
+
+/// Synchronous execution of `sql` with `params` bound to its placeholders
+fn execute_query_with_params(
+    conn: &Connection,
+    sql: &str,
+    params: &QueryParams,
+    value_format: &ValueFormat,
+) -> Result<QueryResult, AppError> {
     use serde_json::Value as JsonValue;
 
     let start = std::time::Instant::now();
 
-    let conn = duckdb::Connection::open(path)
-        .map_err(|e| format!("Failed to open database: {}", e))?;
-
     let mut stmt = conn.prepare(sql)
-        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+        .map_err(|e| AppError::from_query(e, sql))?;
+    bind_params(&mut stmt, params)?;
 
     // Execute query and collect rows
-    let mut rows_result = stmt.query([])
-        .map_err(|e| format!("Failed to execute query: {}", e))?;
-
-    let mut all_rows: Vec<Vec<JsonValue>> = Vec::new();
-    let mut column_count = 0;
-
-    while let Some(row) = rows_result.next().map_err(|e| format!("Error: {}", e))? {
-        let mut values = Vec::new();
-        let mut col_idx = 0;
-
-        // Use get_ref to probe columns - this tells us if column exists
-        loop {
-            match row.get_ref(col_idx) {
-                Ok(val_ref) => {
-                    // Convert ValueRef to JSON
-                    let json_val = value_ref_to_json(val_ref);
-                    values.push(json_val);
-                    col_idx += 1;
-                }
-                Err(_) => {
-                    // Column doesn't exist - we've reached the end
-                    break;
-                }
-            }
-            if col_idx > 500 {
-                break;
-            }
-        }
-
-        if column_count == 0 {
-            column_count = col_idx;
-        }
-        all_rows.push(values);
+    stmt.raw_execute()
+        .map_err(|e| AppError::from_query(e, sql))?;
+    let mut chunks = Vec::new();
+    while let Some(chunk) = stmt.step() {
+        chunks.push(RecordBatch::from(chunk));
     }
 
-    // Release borrow on statement
-    drop(rows_result);
+    // Column names and types come from the statement, so they are known even without rows
+    let columns = stmt.column_names();
+    let column_types = column_types(&stmt, chunks.first());
 
-    // Get column names (statement is executed now)
-    let columns: Vec<String> = (0..column_count)
-        .map(|i| {
-            stmt.column_name(i)
-                .map(|s| s.to_string())
-                .unwrap_or_else(|_| format!("column_{}", i))
-        })
+    let all_rows: Vec<Vec<JsonValue>> = chunks
+        .iter()
+        .flat_map(|chunk| (0..chunk.num_rows()).map(move |row| (chunk, row)))
+        .map(|(chunk, row)| chunk_row_to_json(chunk, row, &column_types, value_format))
         .collect();
 
-    // Infer types from first row
-    let column_types: Vec<String> = if let Some(first_row) = all_rows.first() {
-        first_row.iter().map(infer_type_from_value).collect()
-    } else {
-        vec!["Unknown".to_string(); column_count]
-    };
-
     let row_count = all_rows.len();
     let execution_time_ms = start.elapsed().as_millis();
 
@@ -259,98 +663,101 @@ fn execute_query_sync(path: &std::path::Path, sql: &str) -> Result<QueryResult,
     })
 }
 
-/// Convert DuckDB ValueRef to JSON Value
-fn value_ref_to_json(val_ref: duckdb::types::ValueRef) -> serde_json::Value {
-    use duckdb::types::ValueRef;
-    use serde_json::Value as JsonValue;
-
-    match val_ref {
-        ValueRef::Null => JsonValue::Null,
-        ValueRef::Boolean(b) => JsonValue::from(b),
-        ValueRef::TinyInt(i) => JsonValue::from(i),
-        ValueRef::SmallInt(i) => JsonValue::from(i),
-        ValueRef::Int(i) => JsonValue::from(i),
-        ValueRef::BigInt(i) => JsonValue::from(i),
-        ValueRef::HugeInt(i) => JsonValue::from(i.to_string()),
-        ValueRef::UTinyInt(i) => JsonValue::from(i),
-        ValueRef::USmallInt(i) => JsonValue::from(i),
-        ValueRef::UInt(i) => JsonValue::from(i),
-        ValueRef::UBigInt(i) => JsonValue::from(i),
-        ValueRef::Float(f) => serde_json::Number::from_f64(f as f64)
-            .map(JsonValue::Number)
-            .unwrap_or(JsonValue::Null),
-        ValueRef::Double(f) => serde_json::Number::from_f64(f)
-            .map(JsonValue::Number)
-            .unwrap_or(JsonValue::Null),
-        ValueRef::Decimal(d) => JsonValue::from(d.to_string()),
-        ValueRef::Text(bytes) => JsonValue::from(String::from_utf8_lossy(bytes).to_string()),
-        ValueRef::Blob(bytes) => JsonValue::from(format!("<{} bytes>", bytes.len())),
-        ValueRef::Timestamp(_, micros) => {
-            // Convert microseconds to readable format
-            let secs = micros / 1_000_000;
-            let nsecs = ((micros % 1_000_000) * 1000) as u32;
-            if let Some(dt) = chrono::DateTime::from_timestamp(secs, nsecs) {
-                JsonValue::from(dt.format("%Y-%m-%d %H:%M:%S").to_string())
-            } else {
-                JsonValue::from(micros.to_string())
-            }
-        }
-        ValueRef::Date32(days) => {
-            // Days since 1970-01-01
-            if let Some(date) = chrono::NaiveDate::from_ymd_opt(1970, 1, 1)
-                .and_then(|d| d.checked_add_days(chrono::Days::new(days as u64)))
-            {
-                JsonValue::from(date.format("%Y-%m-%d").to_string())
-            } else {
-                JsonValue::from(days.to_string())
-            }
-        }
-        ValueRef::Time64(_, micros) => {
-            let secs = (micros / 1_000_000) as u32;
-            let nanos = ((micros % 1_000_000) * 1000) as u32;
-            if let Some(time) = chrono::NaiveTime::from_num_seconds_from_midnight_opt(secs, nanos) {
-                JsonValue::from(time.format("%H:%M:%S").to_string())
-            } else {
-                JsonValue::from(micros.to_string())
-            }
-        }
-        ValueRef::Interval { months, days, nanos } => {
-            JsonValue::from(format!("{}m {}d {}ns", months, days, nanos))
-        }
-        ValueRef::Enum(e, _) => JsonValue::from(format!("{:?}", e)),
-        _ => JsonValue::Null,
-    }
+/// Whether `table_name` is a base table, whose rows have a `rowid`, rather
+/// than a view
+fn is_base_table(
+    conn: &Connection,
+    table_name: &str,
+    database: Option<&str>,
+    schema: Option<&str>,
+) -> Result<bool, AppError> {
+    let (default_database, default_schema) = databases::current_catalog(conn)?;
+    let count: i64 = conn.query_row(
+        "SELECT count(*) FROM duckdb_tables() WHERE database_name = ? AND schema_name = ? AND table_name = ?",
+        duckdb::params![database.unwrap_or(&default_database), schema.unwrap_or(&default_schema), table_name],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
 }
 
-/// Infer type from JSON value
-fn infer_type_from_value(value: &serde_json::Value) -> String {
-    match value {
-        serde_json::Value::Null => "Null".to_string(),
-        serde_json::Value::Bool(_) => "Boolean".to_string(),
-        serde_json::Value::Number(n) => {
-            if n.is_i64() {
-                "Integer".to_string()
-            } else {
-                "Float".to_string()
-            }
-        }
-        serde_json::Value::String(_) => "String".to_string(),
-        serde_json::Value::Array(_) => "Array".to_string(),
-        serde_json::Value::Object(_) => "Object".to_string(),
-    }
+/// Rows `offset..offset + limit` of `table` (a quoted, possibly qualified
+/// name) passing the filter of `options`, in its order, with the number of
+/// rows passing the filter. With `with_rowid` the page also carries the
+/// `rowid` of each row, and rows are ordered by `rowid` after the sort keys
+/// so pages do not overlap where those tie.
+fn table_page(
+    conn: &Connection,
+    table: &str,
+    with_rowid: bool,
+    options: &TableDataOptions,
+    limit: usize,
+    offset: usize,
+    value_format: &ValueFormat,
+) -> Result<TablePage, AppError> {
+    let mut params = Vec::new();
+    let where_sql = match &options.filter {
+        Some(filter) => format!(" WHERE {}", filter_sql(filter, &mut params)?),
+        None => String::new(),
+    };
+    let keys = order_by_sql(&options.sort)
+        .into_iter()
+        .chain(with_rowid.then(|| "rowid".to_string()))
+        .collect::<Vec<_>>();
+    let order_sql = if keys.is_empty() {
+        String::new()
+    } else {
+        format!(" ORDER BY {}", keys.join(", "))
+    };
+    let sql = format!(
+        "SELECT {}* FROM {}{}{} LIMIT {} OFFSET {}",
+        if with_rowid { "rowid, " } else { "" },
+        table,
+        where_sql,
+        order_sql,
+        limit,
+        offset
+    );
+    let count_sql = format!("SELECT count(*) FROM {}{}", table, where_sql);
+    let params = QueryParams::Positional(params);
+
+    let mut result = execute_query_with_params(conn, &sql, &params, value_format)?;
+    let rowids = with_rowid.then(|| {
+        result.columns.remove(0);
+        result.column_types.remove(0);
+        result
+            .rows
+            .iter_mut()
+            .map(|row| row.remove(0).as_i64().unwrap_or_default())
+            .collect()
+    });
+
+    let mut stmt = conn.prepare(&count_sql).map_err(|e| AppError::from_query(e, &count_sql))?;
+    bind_params(&mut stmt, &params)?;
+    stmt.raw_execute().map_err(|e| AppError::from_query(e, &count_sql))?;
+    let total_rows = match stmt.raw_query().next()? {
+        Some(row) => row.get(0)?,
+        None => 0,
+    };
+
+    Ok(TablePage {
+        result,
+        total_rows,
+        rowids,
+    })
 }
 
-/// Get table names from connection
-fn get_table_names(conn: &duckdb::Connection) -> Result<Vec<String>, String> {
-    let sql = "SELECT table_name FROM information_schema.tables WHERE table_schema = 'main' AND table_type = 'BASE TABLE'";
-    let mut stmt = conn.prepare(sql)
-        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+/// Names of the tables and views of the default database, qualified with
+/// their schema outside `main`
+fn get_table_names(conn: &Connection) -> Result<Vec<String>, AppError> {
+    let sql = "SELECT CASE WHEN table_schema = 'main' THEN table_name ELSE table_schema || '.' || table_name END
+               FROM information_schema.tables
+               WHERE table_catalog = current_database()
+               ORDER BY table_schema <> 'main', table_schema, table_name";
+    let mut stmt = conn.prepare(sql)?;
 
-    let tables: Vec<String> = stmt
-        .query_map([], |row| row.get(0))
-        .map_err(|e| format!("Failed to execute query: {}", e))?
-        .filter_map(|r| r.ok())
-        .collect();
+    let tables = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
 
     Ok(tables)
 }
@@ -365,17 +772,20 @@ pub async fn export_data(
     file_path: String,
     query: String,
     db: State<'_, DatabaseManager>,
-) -> Result<(), String> {
-    let path = db.get_path().ok_or("No database selected")?;
+    settings: State<'_, SettingsManager>,
+) -> Result<(), AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Export);
+    let value_format = ValueFormat::new(&settings.display(), ResultEncoding::Plain);
 
-    run_blocking(move || {
-        let result = execute_query_sync(&path, &query)?;
+    run_query_blocking(handle, None, time_limit, move |conn| {
+        let result = execute_query_sync(conn, &query, &value_format)?;
 
         match format {
             ExportFormat::Csv => export_to_csv(&result, &file_path),
             ExportFormat::Excel => export_to_excel(&result, &file_path),
             ExportFormat::Json => export_to_json(&result, &file_path),
-            ExportFormat::Parquet => export_to_parquet_via_duckdb(&path, &query, &file_path),
+            ExportFormat::Parquet => export_to_parquet_via_duckdb(conn, &query, &file_path),
         }
     })
     .await
@@ -389,20 +799,20 @@ pub async fn export_data(
 pub async fn import_csv(
     file_path: String,
     table_name: String,
+    database: Option<String>,
+    schema: Option<String>,
     db: State<'_, DatabaseManager>,
-) -> Result<(), String> {
-    let path = db.get_path().ok_or("No database selected")?;
-
-    run_blocking(move || {
-        let conn = duckdb::Connection::open(&path)
-            .map_err(|e| format!("Failed to open database: {}", e))?;
+    settings: State<'_, SettingsManager>,
+) -> Result<(), AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Import);
 
+    run_query_blocking(handle, None, time_limit, move |conn| {
         let sql = format!(
-            "CREATE TABLE IF NOT EXISTS \"{}\" AS SELECT * FROM read_csv_auto('{}')",
-            table_name, file_path
+            "CREATE TABLE IF NOT EXISTS {} AS SELECT * FROM read_csv_auto({})",
+            qualified_name(database.as_deref(), schema.as_deref(), &table_name), quote_path(&file_path)
         );
-        conn.execute(&sql, [])
-            .map_err(|e| format!("Failed to import CSV: {}", e))?;
+        conn.execute(&sql, [])?;
 
         Ok(())
     })
@@ -413,20 +823,20 @@ pub async fn import_csv(
 pub async fn import_parquet(
     file_path: String,
     table_name: String,
+    database: Option<String>,
+    schema: Option<String>,
     db: State<'_, DatabaseManager>,
-) -> Result<(), String> {
-    let path = db.get_path().ok_or("No database selected")?;
-
-    run_blocking(move || {
-        let conn = duckdb::Connection::open(&path)
-            .map_err(|e| format!("Failed to open database: {}", e))?;
+    settings: State<'_, SettingsManager>,
+) -> Result<(), AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Import);
 
+    run_query_blocking(handle, None, time_limit, move |conn| {
         let sql = format!(
-            "CREATE TABLE IF NOT EXISTS \"{}\" AS SELECT * FROM read_parquet('{}')",
-            table_name, file_path
+            "CREATE TABLE IF NOT EXISTS {} AS SELECT * FROM read_parquet({})",
+            qualified_name(database.as_deref(), schema.as_deref(), &table_name), quote_path(&file_path)
         );
-        conn.execute(&sql, [])
-            .map_err(|e| format!("Failed to import Parquet: {}", e))?;
+        conn.execute(&sql, [])?;
 
         Ok(())
     })
@@ -437,36 +847,63 @@ pub async fn import_parquet(
 pub async fn import_excel(
     file_path: String,
     table_name: String,
+    database: Option<String>,
+    schema: Option<String>,
     sheet_name: Option<String>,
     db: State<'_, DatabaseManager>,
-) -> Result<(), String> {
-    let path = db.get_path().ok_or("No database selected")?;
-
-    run_blocking(move || {
-        let conn = duckdb::Connection::open(&path)
-            .map_err(|e| format!("Failed to open database: {}", e))?;
+    settings: State<'_, SettingsManager>,
+) -> Result<(), AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Import);
 
+    run_query_blocking(handle, None, time_limit, move |conn| {
         conn.execute("INSTALL spatial", [])
-            .map_err(|e| format!("Failed to install spatial extension: {}", e))?;
+            .map_err(|e| AppError::Extension {
+                message: format!("Failed to install spatial extension: {}", e),
+            })?;
         conn.execute("LOAD spatial", [])
-            .map_err(|e| format!("Failed to load spatial extension: {}", e))?;
+            .map_err(|e| AppError::Extension {
+                message: format!("Failed to load spatial extension: {}", e),
+            })?;
 
         let sheet_clause = sheet_name
-            .map(|s| format!(", sheet_name='{}'", s))
+            .map(|s| format!(", sheet_name={}", quote_literal(&s)))
             .unwrap_or_default();
 
         let sql = format!(
-            "CREATE TABLE IF NOT EXISTS \"{}\" AS SELECT * FROM st_read('{}'{});",
-            table_name, file_path, sheet_clause
+            "CREATE TABLE IF NOT EXISTS {} AS SELECT * FROM st_read({}{});",
+            qualified_name(database.as_deref(), schema.as_deref(), &table_name), quote_path(&file_path), sheet_clause
         );
-        conn.execute(&sql, [])
-            .map_err(|e| format!("Failed to import Excel: {}", e))?;
+        conn.execute(&sql, [])?;
 
         Ok(())
     })
     .await
 }
 
+/// Create a view over CSV, Parquet or JSON files so they can be browsed and
+/// queried in place instead of imported. `paths` may contain globs. The view
+/// is temporary unless `temporary` is false, in which case it is stored in
+/// the database file.
+#[tauri::command]
+pub async fn register_file_view(
+    name: String,
+    paths: Vec<String>,
+    format: FileViewFormat,
+    temporary: Option<bool>,
+    db: State<'_, DatabaseManager>,
+    settings: State<'_, SettingsManager>,
+) -> Result<(), AppError> {
+    let handle = db.get()?;
+    let temporary = temporary.unwrap_or(true);
+    let time_limit = settings.timeout(OperationKind::Import);
+
+    run_query_blocking(handle, None, time_limit, move |conn| {
+        file_views::register_file_view(conn, &name, &paths, format, temporary)
+    })
+    .await
+}
+
 // ============================================================================
 // Sample Data Commands
 // ============================================================================
@@ -475,13 +912,12 @@ pub async fn import_excel(
 pub async fn create_sample_data(
     sample_type: String,
     db: State<'_, DatabaseManager>,
-) -> Result<(), String> {
-    let path = db.get_path().ok_or("No database selected")?;
-
-    run_blocking(move || {
-        let conn = duckdb::Connection::open(&path)
-            .map_err(|e| format!("Failed to open database: {}", e))?;
+    settings: State<'_, SettingsManager>,
+) -> Result<(), AppError> {
+    let handle = db.get()?;
+    let time_limit = settings.timeout(OperationKind::Import);
 
+    run_query_blocking(handle, None, time_limit, move |conn| {
         let sql = match sample_type.as_str() {
             "users" => r#"
                 CREATE TABLE IF NOT EXISTS users (
@@ -578,20 +1014,32 @@ pub async fn create_sample_data(
                 DELETE FROM orders;
                 INSERT INTO orders SELECT i, 1 + (i % 100), 1 + (i % 50), 1 + (i % 10), ROUND(100 + (random() * 900), 2), CASE (i % 4) WHEN 0 THEN 'pending' WHEN 1 THEN 'processing' WHEN 2 THEN 'shipped' ELSE 'delivered' END, CURRENT_DATE - INTERVAL (i % 365) DAY FROM generate_series(1, 500) as t(i);
             "#,
-            _ => return Err(format!("Unknown sample type: {}", sample_type)),
+            _ => return Err(AppError::invalid_input(format!("Unknown sample type: {}", sample_type))),
         };
 
-        // Execute each statement separately
-        for statement in sql.split(';').filter(|s| !s.trim().is_empty()) {
-            conn.execute(statement.trim(), [])
-                .map_err(|e| format!("Failed to create sample data: {}", e))?;
-        }
-
-        Ok(())
+        Ok(conn.execute_batch(sql)?)
     })
     .await
 }
 
+// ============================================================================
+// Settings Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_settings(settings: State<'_, SettingsManager>) -> Result<Settings, AppError> {
+    Ok(settings.get())
+}
+
+#[tauri::command]
+pub async fn update_settings(
+    new_settings: Settings,
+    app: AppHandle,
+    settings: State<'_, SettingsManager>,
+) -> Result<(), AppError> {
+    settings.update(&app, new_settings)
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -599,47 +1047,312 @@ pub async fn create_sample_data(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::Path;
+
+    fn sample_connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER, name VARCHAR, city VARCHAR);
+             INSERT INTO users SELECT i, 'User ' || i, 'Tokyo' FROM generate_series(1, 10) t(i);",
+        )
+        .unwrap();
+        conn
+    }
 
     #[test]
     fn test_execute_query_sync() {
-        let db_path = Path::new("/Users/kasamatsu/src/github/duckdb-viewer/sample2.duckdb");
-
-        let result = execute_query_sync(db_path, "SELECT id, name, city FROM users LIMIT 5");
-
-        match result {
-            Ok(qr) => {
-                println!("Columns: {:?}", qr.columns);
-                println!("Column types: {:?}", qr.column_types);
-                println!("Row count: {}", qr.row_count);
-                println!("Rows:");
-                for (i, row) in qr.rows.iter().enumerate() {
-                    println!("  Row {}: {:?}", i, row);
-                }
-                assert!(qr.row_count > 0, "Expected some rows");
-                assert_eq!(qr.columns.len(), 3, "Expected 3 columns");
-            }
-            Err(e) => {
-                panic!("Query failed: {}", e);
-            }
+        let conn = sample_connection();
+
+        let sql = "SELECT id, name, city FROM users ORDER BY id LIMIT 2";
+        let result = execute_query_sync(&conn, sql, &ValueFormat::default()).unwrap();
+
+        assert_eq!(result.columns, ["id", "name", "city"]);
+        assert_eq!(result.column_types, ["INTEGER", "VARCHAR", "VARCHAR"]);
+        assert_eq!(result.row_count, 2);
+        assert_eq!(
+            result.rows,
+            [
+                vec![serde_json::json!(1), serde_json::json!("User 1"), serde_json::json!("Tokyo")],
+                vec![serde_json::json!(2), serde_json::json!("User 2"), serde_json::json!("Tokyo")],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wide_and_empty_results_keep_all_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        let select_list = (0..800)
+            .map(|i| format!("{} AS c{}", i, i))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let wide =
+            execute_query_sync(&conn, &format!("SELECT {}", select_list), &ValueFormat::default())
+                .unwrap();
+        assert_eq!(wide.columns.len(), 800);
+        assert_eq!(wide.rows[0].len(), 800);
+        assert_eq!(wide.rows[0][799], serde_json::Value::from(799));
+
+        let empty = execute_query_sync(
+            &conn,
+            &format!("SELECT {} WHERE false", select_list),
+            &ValueFormat::default(),
+        )
+        .unwrap();
+        assert_eq!(empty.row_count, 0);
+        assert_eq!(empty.columns.len(), 800);
+        assert_eq!(empty.columns[799], "c799");
+    }
+
+    #[test]
+    fn test_cancel_query() {
+        let handle = Arc::new(DatabaseHandle::open(PathBuf::from(":memory:"), AccessMode::ReadWrite).unwrap());
+
+        // Unknown queries cannot be cancelled
+        assert!(!handle.cancel_query("missing"));
+
+        // A query cancelled while waiting never runs
+        let query = handle.register_query("waiting".to_string());
+        assert!(handle.cancel_query("waiting"));
+        let result = query.run(|_| Ok(()));
+        assert!(matches!(result.unwrap_err(), AppError::Cancelled));
+
+        // A running query is interrupted
+        let query = handle.register_query("running".to_string());
+        let worker = std::thread::spawn(move || {
+            query.run(|conn| {
+                let sql = "SELECT SUM(i) FROM range(100000000000) t(i)";
+                execute_query_sync(conn, sql, &ValueFormat::default())
+            })
+        });
+        while !handle.is_running("running") {
+            std::thread::sleep(Duration::from_millis(5));
         }
+        assert!(matches!(cancel_until_done(&handle, "running", worker).unwrap_err(), AppError::Cancelled));
+
+        // So are the connections helping it
+        let query = handle.register_query("helped".to_string());
+        let session = Arc::clone(&handle);
+        let (joined, helper_joined) = std::sync::mpsc::channel();
+        let worker = std::thread::spawn(move || {
+            query.run(|conn| {
+                let helper = conn.try_clone()?;
+                let interrupts = session.interrupt_group();
+                assert!(interrupts.add(&helper));
+                joined.send(()).unwrap();
+                let sql = "SELECT SUM(i) FROM range(100000000000) t(i)";
+                execute_query_sync(&helper, sql, &ValueFormat::default())
+            })
+        });
+        helper_joined.recv().unwrap();
+        assert!(matches!(cancel_until_done(&handle, "helped", worker).unwrap_err(), AppError::Cancelled));
+    }
+
+    /// Cancel `query_id` until `worker` running it finishes. DuckDB clears an
+    /// interrupt when a query starts, so one sent just before could be lost.
+    fn cancel_until_done<T>(
+        handle: &DatabaseHandle,
+        query_id: &str,
+        worker: std::thread::JoinHandle<Result<T, AppError>>,
+    ) -> Result<T, AppError> {
+        while !worker.is_finished() {
+            assert!(handle.cancel_query(query_id));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(!handle.cancel_query(query_id));
+        worker.join().unwrap()
+    }
+
+    #[test]
+    fn test_manager_knows_open_file() {
+        let path = std::env::temp_dir().join(format!("duckdb-viewer-reopen-{}.duckdb", std::process::id()));
+        let manager = DatabaseManager::new();
+        manager.set(DatabaseHandle::open(path.clone(), AccessMode::ReadWrite).unwrap());
+
+        let same = path.parent().unwrap().join(".").join(path.file_name().unwrap());
+        assert!(manager.is_open(&same));
+        assert!(!manager.is_open(&path.with_extension("other")));
+        assert!(!manager.is_open(Path::new(IN_MEMORY_PATH)));
+
+        manager.close();
+        assert!(!manager.is_open(&path));
+        std::fs::remove_file(&path).unwrap();
     }
 
     #[test]
-    fn test_value_ref_to_json() {
-        use duckdb::types::ValueRef;
-        use serde_json::Value as JsonValue;
+    fn test_read_only_session() {
+        let path = std::env::temp_dir().join(format!("duckdb-viewer-ro-{}.duckdb", std::process::id()));
+        {
+            let handle = DatabaseHandle::open(path.clone(), AccessMode::ReadWrite).unwrap();
+            handle
+                .connection()
+                .unwrap()
+                .execute_batch("CREATE TABLE t AS SELECT 1 AS i")
+                .unwrap();
+        }
+
+        let handle = DatabaseHandle::open(path.clone(), AccessMode::ReadOnly).unwrap();
+        assert_eq!(handle.access_mode(), AccessMode::ReadOnly);
+
+        let conn = handle.connection().unwrap();
+        let result = execute_query_sync(&conn, "SELECT i FROM t", &ValueFormat::default()).unwrap();
+        assert_eq!(result.row_count, 1);
+        for sql in [
+            "INSERT INTO t VALUES (2)",
+            "CREATE TABLE u (i INTEGER)",
+            "CREATE TABLE IF NOT EXISTS u AS SELECT * FROM range(3)",
+            "CREATE VIEW v AS SELECT 1",
+            "DROP TABLE t",
+        ] {
+            let error = execute_query_sync(&conn, sql, &ValueFormat::default()).unwrap_err();
+            assert_eq!(error.kind(), "readOnly", "{}", sql);
+        }
+
+        // Session-local state is still writable
+        execute_query_sync(&conn, "CREATE TEMP TABLE scratch AS SELECT 1", &ValueFormat::default()).unwrap();
+
+        // A file view can be browsed but not stored in the file
+        let csv = path.with_extension("csv");
+        std::fs::write(&csv, "i\n1\n").unwrap();
+        let paths = [csv.to_string_lossy().into_owned()];
+        file_views::register_file_view(&conn, "fv", &paths, FileViewFormat::Csv, true).unwrap();
+        let error = file_views::register_file_view(&conn, "fv2", &paths, FileViewFormat::Csv, false).unwrap_err();
+        assert_eq!(error.kind(), "readOnly");
+
+        drop(conn);
+        drop(handle);
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&csv).unwrap();
+    }
 
-        // Test null
-        let null_val = value_ref_to_json(ValueRef::Null);
-        assert_eq!(null_val, JsonValue::Null);
+    #[test]
+    fn test_table_page_rowids() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE SCHEMA files;
+             CREATE TABLE files.blobs AS SELECT * FROM (VALUES ('a', 'aa'::BLOB), ('b', 'bb'::BLOB)) v(name, data);
+             CREATE VIEW files.names AS SELECT name FROM files.blobs;",
+        )
+        .unwrap();
+        let options: TableDataOptions =
+            serde_json::from_value(serde_json::json!({"sort": [{"column": "name", "descending": true}]})).unwrap();
+
+        assert!(is_base_table(&conn, "blobs", None, Some("files")).unwrap());
+        let page = table_page(&conn, "files.blobs", true, &options, 10, 0, &ValueFormat::default()).unwrap();
+        assert_eq!(page.result.columns, ["name", "data"]);
+        assert_eq!(page.result.rows[0][0], serde_json::Value::from("b"));
+        let rowid = page.rowids.unwrap()[0];
+        assert_eq!(read_cell_blob(&conn, "blobs", None, Some("files"), rowid, "data").unwrap(), b"bb");
+
+        assert!(!is_base_table(&conn, "names", None, Some("files")).unwrap());
+        let page = table_page(&conn, "files.names", false, &options, 10, 0, &ValueFormat::default()).unwrap();
+        assert_eq!(page.result.columns, ["name"]);
+        assert!(page.rowids.is_none());
+    }
 
-        // Test int
-        let int_val = value_ref_to_json(ValueRef::Int(42));
-        assert_eq!(int_val, JsonValue::from(42));
+    #[test]
+    fn test_table_pages_break_ties_by_rowid() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE ties AS SELECT i % 2 AS parity, i FROM range(2000) t(i)")
+            .unwrap();
+        let sorted: TableDataOptions = serde_json::from_value(serde_json::json!({"sort": [{"column": "parity"}]})).unwrap();
+
+        for options in [TableDataOptions::default(), sorted] {
+            let mut rowids = (0..20)
+                .flat_map(|page| {
+                    table_page(&conn, "ties", true, &options, 100, page * 100, &ValueFormat::default())
+                        .unwrap()
+                        .rowids
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            rowids.sort_unstable();
+            rowids.dedup();
+            assert_eq!(rowids.len(), 2000);
+        }
+    }
+
+    #[test]
+    fn test_cursor_fetches_in_batches() {
+        let handle = DatabaseHandle::open(PathBuf::from(":memory:"), AccessMode::ReadWrite).unwrap();
+        let conn = handle.connection().unwrap();
+        conn.execute_batch("CREATE TABLE numbers AS SELECT i FROM range(25) t(i)").unwrap();
+
+        let cursor_id = handle.reserve_cursor().unwrap();
+        let info = handle
+            .open_cursor(&conn, cursor_id, "SELECT i FROM numbers ORDER BY i", ValueFormat::default())
+            .unwrap();
+        drop(conn);
+        assert_eq!(info.columns, vec!["i"]);
+
+        let cursor = handle.cursor(&info.cursor_id).unwrap();
+        let first = cursor.fetch(10);
+        assert_eq!(first.row_count, 10);
+        assert_eq!(first.rows[0][0], serde_json::Value::from(0));
+        assert!(!first.done);
+
+        let second = cursor.fetch(100);
+        assert_eq!(second.row_count, 15);
+        assert_eq!(second.rows[14][0], serde_json::Value::from(24));
+        assert!(second.done);
+
+        assert!(handle.close_cursor(&info.cursor_id));
+        assert!(handle.cursor(&info.cursor_id).is_err());
+    }
+
+    #[test]
+    fn test_close_cursor_while_opening() {
+        let handle = Arc::new(DatabaseHandle::open(PathBuf::from(":memory:"), AccessMode::ReadWrite).unwrap());
+        let cursor_id = handle.reserve_cursor().unwrap();
+
+        let query = handle.register_query(cursor_id.clone());
+        let opener = Arc::clone(&handle);
+        let id = cursor_id.clone();
+        let worker = std::thread::spawn(move || {
+            let sql = "SELECT SUM(i) FROM range(100000000000) t(i)";
+            query.run(|conn| opener.open_cursor(conn, id, sql, ValueFormat::default()))
+        });
+        while !handle.is_running(&cursor_id) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        // Closing cancels the query; the cursor is never registered
+        assert!(handle.close_cursor(&cursor_id));
+        assert!(matches!(worker.join().unwrap().unwrap_err(), AppError::Cancelled));
+        assert!(handle.cursor(&cursor_id).is_err());
+        assert!(!handle.close_cursor(&cursor_id));
+    }
+
+    #[test]
+    fn test_cursor_sees_session_state() {
+        let handle = DatabaseHandle::open(PathBuf::from(":memory:"), AccessMode::ReadWrite).unwrap();
+        let conn = handle.connection().unwrap();
+        conn.execute_batch("CREATE TEMP TABLE scratch AS SELECT 7 AS n; SET VARIABLE answer = 42").unwrap();
+
+        let sql = "SELECT n, getvariable('answer') AS answer, NULL AS nothing FROM scratch";
+        let info = handle
+            .open_cursor(&conn, handle.reserve_cursor().unwrap(), sql, ValueFormat::default())
+            .unwrap();
+        drop(conn);
+
+        let batch = handle.cursor(&info.cursor_id).unwrap().fetch(10);
+        assert!(batch.done);
+        assert_eq!(
+            batch.rows,
+            vec![vec![
+                serde_json::Value::from(7),
+                serde_json::Value::from(42),
+                serde_json::Value::Null,
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_open_cursors_are_limited() {
+        let handle = DatabaseHandle::open(PathBuf::from(":memory:"), AccessMode::ReadWrite).unwrap();
+        let cursor_ids = (0..crate::db::MAX_OPEN_CURSORS).map(|_| handle.reserve_cursor().unwrap()).collect::<Vec<_>>();
+        assert!(matches!(handle.reserve_cursor(), Err(AppError::InvalidInput { .. })));
 
-        // Test text
-        let text_val = value_ref_to_json(ValueRef::Text(b"hello"));
-        assert_eq!(text_val, JsonValue::from("hello"));
+        assert!(handle.close_cursor(&cursor_ids[0]));
+        assert!(handle.reserve_cursor().is_ok());
     }
 }
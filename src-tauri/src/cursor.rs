@@ -0,0 +1,103 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use duckdb::arrow::record_batch::RecordBatch;
+use duckdb::Connection;
+
+use crate::error::AppError;
+use crate::models::RowBatch;
+use crate::types::column_types;
+use crate::values::{chunk_row_to_json, ValueFormat};
+
+/// Column names and types of a cursor's result
+pub type CursorColumns = (Vec<String>, Vec<String>);
+
+/// A server-side cursor: a query result kept as Arrow batches so rows can be
+/// fetched and converted to JSON a batch at a time instead of all at once.
+///
+/// The query runs on the session connection like any other, so it sees the
+/// session's temporary tables and SET variables. Once it has run the cursor
+/// no longer needs the connection, and fetches do not hold it up.
+pub struct Cursor {
+    column_types: Vec<String>,
+    value_format: ValueFormat,
+    state: Mutex<CursorState>,
+}
+
+struct CursorState {
+    /// Batches not yet fully fetched; the first is partly read up to `next_row`
+    batches: VecDeque<RecordBatch>,
+    next_row: usize,
+    last_used: Instant,
+}
+
+impl Cursor {
+    /// Execute `sql` on `conn` and keep its result
+    pub fn open(conn: &Connection, sql: &str, value_format: ValueFormat) -> Result<(Self, CursorColumns), AppError> {
+        let mut stmt = conn.prepare(sql).map_err(|e| AppError::from_query(e, sql))?;
+        let batches = stmt
+            .query_arrow([])
+            .map_err(|e| AppError::from_query(e, sql))?
+            .collect::<VecDeque<_>>();
+
+        let columns = stmt.column_names();
+        let column_types = column_types(&stmt, batches.front());
+
+        let cursor = Self {
+            column_types: column_types.clone(),
+            value_format,
+            state: Mutex::new(CursorState {
+                batches,
+                next_row: 0,
+                last_used: Instant::now(),
+            }),
+        };
+        Ok((cursor, (columns, column_types)))
+    }
+
+    /// Fetch up to `max_rows` further rows
+    pub fn fetch(&self, max_rows: usize) -> RowBatch {
+        let mut state = self.state();
+        state.last_used = Instant::now();
+
+        let mut rows = Vec::new();
+        while rows.len() < max_rows {
+            let next_row = state.next_row;
+            let Some(batch) = state.batches.front() else {
+                break;
+            };
+            if next_row >= batch.num_rows() {
+                state.batches.pop_front();
+                state.next_row = 0;
+                continue;
+            }
+
+            let end = batch.num_rows().min(next_row + max_rows - rows.len());
+            rows.extend((next_row..end).map(|row| chunk_row_to_json(batch, row, &self.column_types, &self.value_format)));
+            state.next_row = end;
+        }
+
+        // Drop a used-up batch now so `done` is reported with the last rows
+        while state.batches.front().is_some_and(|batch| state.next_row >= batch.num_rows()) {
+            state.batches.pop_front();
+            state.next_row = 0;
+        }
+
+        RowBatch {
+            row_count: rows.len(),
+            rows,
+            done: state.batches.is_empty(),
+        }
+    }
+
+    /// How long since the cursor was opened or last fetched from
+    pub fn idle_for(&self) -> Duration {
+        self.state().last_used.elapsed()
+    }
+
+    fn state(&self) -> MutexGuard<'_, CursorState> {
+        // Fetches leave the state consistent between rows, so recover from poisoning
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
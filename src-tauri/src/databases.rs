@@ -0,0 +1,309 @@
+use std::path::Path;
+
+use duckdb::Connection;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{AccessMode, AttachedDatabase, SourceType};
+use crate::quote::{quote_identifier, quote_literal, quote_path};
+
+/// Databases of the session: the opened file plus everything ATTACHed to it,
+/// default first. DuckDB's internal `system` and `temp` catalogs are left out.
+pub fn list_databases(conn: &Connection) -> Result<Vec<AttachedDatabase>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT database_name, path, type, readonly, database_name = current_database() AS is_default
+         FROM duckdb_databases()
+         WHERE NOT internal
+         ORDER BY is_default DESC, database_name",
+    )?;
+
+    let databases = stmt
+        .query_map([], |row| {
+            Ok(AttachedDatabase {
+                name: row.get(0)?,
+                path: row.get(1)?,
+                source_type: row.get(2)?,
+                access_mode: if row.get(3)? {
+                    AccessMode::ReadOnly
+                } else {
+                    AccessMode::ReadWrite
+                },
+                is_default: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(databases)
+}
+
+/// ATTACH the DuckDB database file at `path` as `alias`, which defaults to
+/// the file name without its extension
+pub fn attach_database(
+    conn: &Connection,
+    path: &str,
+    alias: Option<&str>,
+    access_mode: AccessMode,
+) -> Result<AttachedDatabase, AppError> {
+    attach_source(conn, SourceType::Duckdb, path, alias, access_mode)
+}
+
+/// ATTACH an external database through DuckDB's scanner extension for
+/// `source_type`, installing and loading the extension first. `location` is
+/// a file path or, for Postgres and MySQL, a connection string. The alias
+/// defaults to the file name without its extension; connection strings need
+/// an explicit one.
+pub fn attach_source(
+    conn: &Connection,
+    source_type: SourceType,
+    location: &str,
+    alias: Option<&str>,
+    access_mode: AccessMode,
+) -> Result<AttachedDatabase, AppError> {
+    let alias = match (alias, source_type) {
+        (Some(alias), _) => alias.to_string(),
+        (None, SourceType::Duckdb | SourceType::Sqlite) => Path::new(location)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .ok_or_else(|| AppError::invalid_input(format!("Cannot derive an alias from {}", location)))?,
+        (None, SourceType::Postgres | SourceType::Mysql) => {
+            return Err(AppError::invalid_input("An alias is required for Postgres and MySQL sources"))
+        }
+    };
+
+    let mut options = Vec::new();
+    if let Some(extension) = scanner_extension(source_type) {
+        load_extension(conn, extension)?;
+        options.push(format!("TYPE {}", extension));
+    }
+    if access_mode == AccessMode::ReadOnly {
+        options.push("READ_ONLY".to_string());
+    }
+
+    let location = match source_type {
+        SourceType::Duckdb | SourceType::Sqlite => quote_path(location),
+        SourceType::Postgres | SourceType::Mysql => quote_literal(location),
+    };
+    let options = if options.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", options.join(", "))
+    };
+    conn.execute_batch(&format!(
+        "ATTACH {} AS {}{}",
+        location,
+        quote_identifier(&alias),
+        options
+    ))?;
+
+    find_database(conn, &alias)
+}
+
+/// Extension providing the storage type of `source_type`; DuckDB files need none
+fn scanner_extension(source_type: SourceType) -> Option<&'static str> {
+    match source_type {
+        SourceType::Duckdb => None,
+        SourceType::Sqlite => Some("sqlite"),
+        SourceType::Postgres => Some("postgres"),
+        SourceType::Mysql => Some("mysql"),
+    }
+}
+
+fn load_extension(conn: &Connection, extension: &str) -> Result<(), AppError> {
+    conn.execute_batch(&format!("INSTALL {0}; LOAD {0};", extension))
+        .map_err(|e| AppError::Extension {
+            message: format!("Failed to load {} extension: {}", extension, e),
+        })
+}
+
+/// DETACH the database attached as `name`
+pub fn detach_database(conn: &Connection, name: &str) -> Result<(), AppError> {
+    conn.execute_batch(&format!("DETACH {}", quote_identifier(name)))?;
+    Ok(())
+}
+
+/// Copy every schema, table, view, sequence and macro of database `source`
+/// into a new DuckDB file at `path`. The file must not exist yet; it is
+/// removed again if the copy fails.
+pub fn persist_database(conn: &Connection, source: &str, path: &str) -> Result<(), AppError> {
+    if Path::new(path).exists() {
+        return Err(AppError::invalid_input(format!("{} already exists", path)));
+    }
+
+    let target = quote_identifier(&format!("persist_{}", Uuid::new_v4().simple()));
+    conn.execute_batch(&format!("ATTACH {} AS {}", quote_path(path), target))?;
+
+    let copied = conn.execute_batch(&format!(
+        "COPY FROM DATABASE {} TO {}",
+        quote_identifier(source),
+        target
+    ));
+    let detached = conn.execute_batch(&format!("DETACH {}", target));
+
+    if let Err(e) = copied.and(detached) {
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(format!("{}.wal", path));
+        return Err(e.into());
+    }
+
+    Ok(())
+}
+
+/// Make `name` (optionally one of its schemas) the default for unqualified
+/// table names
+pub fn use_database(conn: &Connection, name: &str, schema: Option<&str>) -> Result<AttachedDatabase, AppError> {
+    let target = match schema {
+        Some(schema) => format!("{}.{}", quote_identifier(name), quote_identifier(schema)),
+        None => quote_identifier(name),
+    };
+    conn.execute_batch(&format!("USE {}", target))?;
+
+    find_database(conn, name)
+}
+
+/// The session's default database and schema, as set by `USE`
+pub fn current_catalog(conn: &Connection) -> Result<(String, String), AppError> {
+    Ok(conn.query_row("SELECT current_database(), current_schema()", [], |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    })?)
+}
+
+fn find_database(conn: &Connection, name: &str) -> Result<AttachedDatabase, AppError> {
+    list_databases(conn)?
+        .into_iter()
+        .find(|database| database.name == name)
+        .ok_or_else(|| AppError::invalid_input(format!("Unknown database: {}", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attach_use_and_detach() {
+        let dir = std::env::temp_dir().join(format!("duckdb-viewer-attach-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let other = dir.join("other's.duckdb");
+        let other = other.to_str().unwrap();
+
+        let conn = Connection::open(dir.join("session.duckdb")).unwrap();
+        let attached = attach_database(&conn, other, None, AccessMode::ReadWrite).unwrap();
+        assert_eq!(attached.name, "other's");
+        assert_eq!(attached.access_mode, AccessMode::ReadWrite);
+        assert!(!attached.is_default);
+
+        conn.execute_batch("CREATE SCHEMA \"other's\".sales; CREATE TABLE \"other's\".sales.orders (id INTEGER)")
+            .unwrap();
+
+        let names: Vec<String> = list_databases(&conn).unwrap().into_iter().map(|d| d.name).collect();
+        assert_eq!(names, vec!["session", "other's"]);
+
+        let switched = use_database(&conn, "other's", Some("sales")).unwrap();
+        assert!(switched.is_default);
+        assert_eq!(current_catalog(&conn).unwrap(), ("other's".to_string(), "sales".to_string()));
+        conn.execute_batch("SELECT * FROM orders").unwrap();
+
+        // The default database cannot be detached
+        assert!(detach_database(&conn, "other's").is_err());
+        use_database(&conn, "session", None).unwrap();
+        detach_database(&conn, "other's").unwrap();
+        assert_eq!(list_databases(&conn).unwrap().len(), 1);
+
+        let read_only = attach_database(&conn, other, Some("snapshot"), AccessMode::ReadOnly).unwrap();
+        assert_eq!(read_only.access_mode, AccessMode::ReadOnly);
+        let error = conn.execute_batch("CREATE TABLE snapshot.t (i INTEGER)").unwrap_err();
+        assert_eq!(AppError::from(error).kind(), "readOnly");
+
+        drop(conn);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_persist_in_memory_database() {
+        let dir = std::env::temp_dir().join(format!("duckdb-viewer-persist-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("scratch.duckdb");
+        let path = path.to_str().unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE SCHEMA staging;
+             CREATE SEQUENCE staging.ids;
+             CREATE TABLE staging.events (id INTEGER DEFAULT nextval('staging.ids'), name VARCHAR);
+             INSERT INTO staging.events (name) VALUES ('open'), ('close');
+             CREATE VIEW event_names AS SELECT name FROM staging.events;
+             CREATE MACRO twice(x) AS x * 2;",
+        )
+        .unwrap();
+
+        persist_database(&conn, "memory", path).unwrap();
+        assert_eq!(list_databases(&conn).unwrap().len(), 1);
+        let error = persist_database(&conn, "memory", path).unwrap_err();
+        assert_eq!(error.kind(), "invalidInput");
+        drop(conn);
+
+        let copy = Connection::open(path).unwrap();
+        let (names, doubled): (String, i32) = copy
+            .query_row(
+                "SELECT string_agg(name, ',' ORDER BY name), twice(21) FROM event_names",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(names, "close,open");
+        assert_eq!(doubled, 42);
+        let next_id: i64 = copy.query_row("SELECT nextval('staging.ids')", [], |row| row.get(0)).unwrap();
+        assert_eq!(next_id, 3);
+
+        drop(copy);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_attach_source_requires_alias_for_connection_strings() {
+        let conn = Connection::open_in_memory().unwrap();
+        let error = attach_source(&conn, SourceType::Postgres, "host=localhost dbname=shop", None, AccessMode::ReadOnly)
+            .unwrap_err();
+        assert_eq!(error.kind(), "invalidInput");
+    }
+
+    #[test]
+    #[ignore = "needs the sqlite extension, which DuckDB downloads on first use"]
+    fn test_attach_sqlite_source() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        let dir = std::env::temp_dir().join(format!("duckdb-viewer-sqlite-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("legacy.sqlite");
+        let path = path.to_str().unwrap();
+
+        // Generate the SQLite file through DuckDB itself
+        let writer = attach_source(&conn, SourceType::Sqlite, path, Some("writer"), AccessMode::ReadWrite).unwrap();
+        assert_eq!(writer.source_type, "sqlite");
+        conn.execute_batch(
+            "CREATE TABLE writer.customers (id INTEGER, name TEXT);
+             INSERT INTO writer.customers VALUES (1, 'Ada'), (2, 'Grace');
+             DETACH writer",
+        )
+        .unwrap();
+
+        let source = attach_source(&conn, SourceType::Sqlite, path, None, AccessMode::ReadOnly).unwrap();
+        assert_eq!(source.name, "legacy");
+        assert_eq!(source.access_mode, AccessMode::ReadOnly);
+
+        let names: i64 = conn
+            .query_row(
+                "SELECT count(*) FROM duckdb_tables() WHERE database_name = 'legacy' AND table_name = 'customers'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(names, 1);
+        let name: String = conn
+            .query_row("SELECT name FROM legacy.customers WHERE id = 2", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(name, "Grace");
+
+        drop(conn);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,26 +1,404 @@
-use std::path::PathBuf;
-use std::sync::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::time::Duration;
 
-/// Simple database path manager using RwLock for thread-safe access
+use duckdb::{Config, Connection, InterruptHandle};
+use uuid::Uuid;
+
+use crate::cursor::Cursor;
+use crate::error::AppError;
+use crate::models::{AccessMode, CurrentDatabase, CursorInfo};
+use crate::values::ValueFormat;
+
+/// Path DuckDB treats as a database that lives only in memory
+pub const IN_MEMORY_PATH: &str = ":memory:";
+
+/// Name DuckDB gives an in-memory database in the session's catalog
+pub const IN_MEMORY_CATALOG: &str = "memory";
+
+/// Catalog holding the session's temporary objects, which connections cloned
+/// from the session connection cannot see
+pub const TEMP_CATALOG: &str = "temp";
+
+/// Appended to the error DuckDB gives when another process holds the lock on
+/// a database file
+const LOCK_CONFLICT_HINT: &str = "DuckDB lets one process write a database file or any number of \
+processes read it, never both at once. Close the file in the other program, or open it read-only \
+in every program that needs it.";
+
+/// Most cursors a session keeps open at once, as each holds its whole result
+pub const MAX_OPEN_CURSORS: usize = 16;
+
+/// How long an open cursor may go without a fetch before it is closed
+pub const CURSOR_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// An open database: the file it was opened from, its access mode and the
+/// long-lived connection that every command borrows, so session state (SET
+/// variables, temp tables, attached databases, loaded extensions) survives
+/// between calls.
+pub struct DatabaseHandle {
+    path: PathBuf,
+    access_mode: AccessMode,
+    conn: Mutex<Connection>,
+    interrupt: Arc<InterruptHandle>,
+    queries: Mutex<QueryRegistry>,
+    cursors: Mutex<HashMap<String, CursorSlot>>,
+}
+
+/// A cursor id in use: reserved by [`DatabaseHandle::reserve_cursor`] and
+/// opening until its query has run
+enum CursorSlot {
+    Opening,
+    Open(Arc<Cursor>),
+}
+
+/// Close the open cursors no one has fetched from for [`CURSOR_IDLE_TIMEOUT`]
+fn expire_idle_cursors(cursors: &mut HashMap<String, CursorSlot>) {
+    cursors.retain(|_, slot| !matches!(slot, CursorSlot::Open(cursor) if cursor.idle_for() >= CURSOR_IDLE_TIMEOUT));
+}
+
+/// Book-keeping for cancellable queries on the session connection
+#[derive(Default)]
+struct QueryRegistry {
+    /// Queries registered but still waiting for the connection
+    pending: HashSet<String>,
+    /// Query currently executing on the connection
+    active: Option<String>,
+    /// Queries asked to stop that have not yet returned
+    cancelled: HashSet<String>,
+    /// Connections helping the active query, see [`DatabaseHandle::interrupt_group`]
+    helpers: Option<Arc<InterruptGroup>>,
+}
+
+/// Connections cloned from the session connection to share the work of the
+/// query running on it. Cancelling that query (or its timing out) interrupts
+/// them too; as DuckDB forgets an interrupt that arrives between two
+/// statements, helpers also check [`InterruptGroup::is_interrupted`] before
+/// starting each one.
+#[derive(Default)]
+pub struct InterruptGroup {
+    state: Mutex<(bool, Vec<Arc<InterruptHandle>>)>,
+}
+
+impl InterruptGroup {
+    /// Interrupt `conn` along with the query; false, adding nothing, if the
+    /// query was already interrupted
+    pub fn add(&self, conn: &Connection) -> bool {
+        let mut state = self.state();
+        if !state.0 {
+            state.1.push(conn.interrupt_handle());
+        }
+        !state.0
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.state().0
+    }
+
+    pub fn interrupt(&self) {
+        let mut state = self.state();
+        state.0 = true;
+        for handle in &state.1 {
+            handle.interrupt();
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, (bool, Vec<Arc<InterruptHandle>>)> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl DatabaseHandle {
+    /// Open the database file at `path`. A read-write open creates the file
+    /// if it does not exist; a read-only one requires it.
+    pub fn open(path: PathBuf, access_mode: AccessMode) -> Result<Self, AppError> {
+        let config = Config::default().access_mode(match access_mode {
+            AccessMode::ReadOnly => duckdb::AccessMode::ReadOnly,
+            AccessMode::ReadWrite => duckdb::AccessMode::ReadWrite,
+        })?;
+        let conn = Connection::open_with_flags(&path, config).map_err(|e| match e {
+            duckdb::Error::DuckDBFailure(code, Some(message)) if message.contains("Could not set lock on file") => {
+                duckdb::Error::DuckDBFailure(code, Some(format!("{}\n{}", message, LOCK_CONFLICT_HINT)))
+            }
+            e => e,
+        })?;
+
+        let interrupt = conn.interrupt_handle();
+
+        Ok(Self {
+            path,
+            access_mode,
+            conn: Mutex::new(conn),
+            interrupt,
+            queries: Mutex::new(QueryRegistry::default()),
+            cursors: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Open a scratch database that lives in memory until it is closed or
+    /// written out with `persist_database`
+    pub fn open_in_memory() -> Result<Self, AppError> {
+        Self::open(PathBuf::from(IN_MEMORY_PATH), AccessMode::ReadWrite)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_in_memory(&self) -> bool {
+        self.path.as_os_str() == IN_MEMORY_PATH
+    }
+
+    pub fn access_mode(&self) -> AccessMode {
+        self.access_mode
+    }
+
+    /// Borrow the session connection, waiting for any command currently using it
+    pub fn connection(&self) -> Result<MutexGuard<'_, Connection>, AppError> {
+        self.conn
+            .lock()
+            .map_err(|_| AppError::internal("Database connection is poisoned"))
+    }
+
+    /// Register `query_id` so it can be cancelled from now on, even before it
+    /// gets the connection. Run it with [`QueryTicket::run`].
+    pub fn register_query(self: &Arc<Self>, query_id: String) -> QueryTicket {
+        self.registry().pending.insert(query_id.clone());
+        QueryTicket {
+            handle: Arc::clone(self),
+            query_id,
+        }
+    }
+
+    /// Stop `query_id`: a waiting query never starts, a running one is
+    /// interrupted. Returns false if no such query is in flight.
+    pub fn cancel_query(&self, query_id: &str) -> bool {
+        let mut registry = self.registry();
+
+        if registry.active.as_deref() == Some(query_id) {
+            self.interrupt.interrupt();
+            if let Some(helpers) = &registry.helpers {
+                helpers.interrupt();
+            }
+        } else if !registry.pending.contains(query_id) {
+            return false;
+        }
+
+        registry.cancelled.insert(query_id.to_string());
+        true
+    }
+
+    /// Whether `query_id` is executing on the session connection, as opposed
+    /// to waiting for it or not registered at all
+    pub fn is_running(&self, query_id: &str) -> bool {
+        self.registry().active.as_deref() == Some(query_id)
+    }
+
+    /// Group for connections helping the query now running on the session
+    /// connection; call it from within that query. It starts interrupted if
+    /// the query already was.
+    pub fn interrupt_group(&self) -> Arc<InterruptGroup> {
+        let mut registry = self.registry();
+        let cancelled = registry.active.as_ref().is_some_and(|active| registry.cancelled.contains(active));
+        let helpers = Arc::clone(registry.helpers.get_or_insert_with(Default::default));
+        if cancelled {
+            helpers.interrupt();
+        }
+        helpers
+    }
+
+    fn registry(&self) -> MutexGuard<'_, QueryRegistry> {
+        // The registry holds no invariants a panic could break, so recover from poisoning
+        self.queries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Reserve the id of a cursor about to be opened with `open_cursor`, so
+    /// it can be closed, cancelling its query, while it is still opening.
+    /// Fails once [`MAX_OPEN_CURSORS`] are open; cursors left idle for
+    /// [`CURSOR_IDLE_TIMEOUT`] are closed first.
+    pub fn reserve_cursor(&self) -> Result<String, AppError> {
+        let mut cursors = self.cursor_map();
+        expire_idle_cursors(&mut cursors);
+        if cursors.len() >= MAX_OPEN_CURSORS {
+            return Err(AppError::invalid_input(format!(
+                "Too many open cursors (at most {}); close some first",
+                MAX_OPEN_CURSORS
+            )));
+        }
+
+        let cursor_id = Uuid::new_v4().to_string();
+        cursors.insert(cursor_id.clone(), CursorSlot::Opening);
+        Ok(cursor_id)
+    }
+
+    /// Execute `sql` on `conn` as the cursor `cursor_id` (see
+    /// `reserve_cursor`), whose rows are fetched in batches and rendered with
+    /// `value_format`. Call it from within the query registered under the
+    /// cursor's id, which `close_cursor` cancels while the cursor is opening.
+    /// Fails with [`AppError::Cancelled`] if the cursor is closed before it opens.
+    pub fn open_cursor(
+        &self,
+        conn: &Connection,
+        cursor_id: String,
+        sql: &str,
+        value_format: ValueFormat,
+    ) -> Result<CursorInfo, AppError> {
+        if !matches!(self.cursor_map().get(&cursor_id), Some(CursorSlot::Opening)) {
+            return Err(AppError::Cancelled);
+        }
+
+        let opened = Cursor::open(conn, sql, value_format);
+
+        let mut cursors = self.cursor_map();
+        if !matches!(cursors.get(&cursor_id), Some(CursorSlot::Opening)) {
+            return Err(AppError::Cancelled);
+        }
+        let (cursor, (columns, column_types)) = match opened {
+            Ok(opened) => opened,
+            Err(e) => {
+                cursors.remove(&cursor_id);
+                return Err(e);
+            }
+        };
+        cursors.insert(cursor_id.clone(), CursorSlot::Open(Arc::new(cursor)));
+
+        Ok(CursorInfo {
+            cursor_id,
+            columns,
+            column_types,
+        })
+    }
+
+    pub fn cursor(&self, cursor_id: &str) -> Result<Arc<Cursor>, AppError> {
+        let mut cursors = self.cursor_map();
+        expire_idle_cursors(&mut cursors);
+        match cursors.get(cursor_id) {
+            Some(CursorSlot::Open(cursor)) => Ok(Arc::clone(cursor)),
+            _ => Err(AppError::invalid_input(format!("Unknown cursor: {}", cursor_id))),
+        }
+    }
+
+    /// Release a cursor, cancelling its query if it is still opening.
+    /// Returns false if it was not open.
+    pub fn close_cursor(&self, cursor_id: &str) -> bool {
+        let slot = self.cursor_map().remove(cursor_id);
+        match slot {
+            Some(CursorSlot::Opening) => {
+                self.cancel_query(cursor_id);
+                true
+            }
+            Some(CursorSlot::Open(_)) => true,
+            None => false,
+        }
+    }
+
+    fn cursor_map(&self) -> MutexGuard<'_, HashMap<String, CursorSlot>> {
+        self.cursors.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A query registered with [`DatabaseHandle::register_query`]; dropping it
+/// unregisters the query.
+pub struct QueryTicket {
+    handle: Arc<DatabaseHandle>,
+    query_id: String,
+}
+
+impl QueryTicket {
+    /// Run `f` on the session connection, returning [`AppError::Cancelled`] if
+    /// the query was cancelled before or while it ran
+    pub fn run<T>(self, f: impl FnOnce(&Connection) -> Result<T, AppError>) -> Result<T, AppError> {
+        let conn = self.handle.connection()?;
+
+        {
+            let mut registry = self.handle.registry();
+            registry.pending.remove(&self.query_id);
+            if registry.cancelled.contains(&self.query_id) {
+                return Err(AppError::Cancelled);
+            }
+            registry.active = Some(self.query_id.clone());
+        }
+
+        let result = f(&conn);
+
+        let mut registry = self.handle.registry();
+        registry.active = None;
+        registry.helpers = None;
+        if registry.cancelled.contains(&self.query_id) {
+            return Err(AppError::Cancelled);
+        }
+        result
+    }
+}
+
+impl Drop for QueryTicket {
+    fn drop(&mut self) {
+        let mut registry = self.handle.registry();
+        registry.pending.remove(&self.query_id);
+        registry.cancelled.remove(&self.query_id);
+    }
+}
+
+/// Owns the currently open database using RwLock for thread-safe access.
+/// Commands clone the `Arc` so a close or reopen never pulls the connection
+/// out from under a running query; it is closed once the last user drops it.
 pub struct DatabaseManager {
-    path: RwLock<Option<PathBuf>>,
+    current: RwLock<Option<Arc<DatabaseHandle>>>,
 }
 
 impl DatabaseManager {
     pub fn new() -> Self {
         Self {
-            path: RwLock::new(None),
+            current: RwLock::new(None),
+        }
+    }
+
+    /// Make `handle` the open database, closing any previous one
+    pub fn set(&self, handle: DatabaseHandle) {
+        if let Ok(mut guard) = self.current.write() {
+            *guard = Some(Arc::new(handle));
         }
     }
 
-    pub fn set_path(&self, path: Option<PathBuf>) {
-        if let Ok(mut guard) = self.path.write() {
-            *guard = path;
+    /// Whether `path` is the file of the open database, which must be closed
+    /// before it can be opened again as DuckDB locks it while it is open
+    pub fn is_open(&self, path: &Path) -> bool {
+        let Ok(handle) = self.get() else {
+            return false;
+        };
+        if handle.is_in_memory() || path.as_os_str() == IN_MEMORY_PATH {
+            return false;
         }
+        match (std::fs::canonicalize(path), std::fs::canonicalize(handle.path())) {
+            (Ok(path), Ok(open)) => path == open,
+            _ => false,
+        }
+    }
+
+    /// Close the open database, if any
+    pub fn close(&self) {
+        if let Ok(mut guard) = self.current.write() {
+            guard.take();
+        }
+    }
+
+    /// Get the open database or fail with [`AppError::NoDatabase`]
+    pub fn get(&self) -> Result<Arc<DatabaseHandle>, AppError> {
+        self.current
+            .read()
+            .ok()
+            .and_then(|guard| guard.clone())
+            .ok_or(AppError::NoDatabase)
     }
 
-    pub fn get_path(&self) -> Option<PathBuf> {
-        self.path.read().ok().and_then(|guard| guard.clone())
+    /// Path and access mode of the open database, if any
+    pub fn current(&self) -> Option<CurrentDatabase> {
+        self.get().ok().map(|handle| CurrentDatabase {
+            path: handle.path().to_string_lossy().to_string(),
+            access_mode: handle.access_mode(),
+            in_memory: handle.is_in_memory(),
+        })
     }
 }
 
@@ -0,0 +1,603 @@
+use std::collections::HashSet;
+
+use duckdb::types::Value;
+use duckdb::{params, Connection};
+
+use crate::databases::current_catalog;
+use crate::error::AppError;
+use crate::models::{CatalogObjectType, ObjectRef};
+use crate::quote::{qualified_name, quote_identifier, quote_literal};
+use crate::table_schema::string_list;
+
+/// One object's CREATE statement followed by the COMMENT ON statements
+/// restoring its comments
+struct Definition {
+    schema: String,
+    name: String,
+    object_type: CatalogObjectType,
+    statements: Vec<String>,
+    /// What the definition refers to, which must be created first
+    references: HashSet<Reference>,
+    /// The indexed table, for indexes
+    table_name: Option<String>,
+}
+
+/// An object a definition refers to, by lowercased name: a table a foreign
+/// key references, which is in the same schema, or a name read from SQL,
+/// which may be in any schema
+#[derive(PartialEq, Eq, Hash)]
+enum Reference {
+    Table(String, String),
+    Name(String),
+}
+
+impl Reference {
+    fn matches(&self, definition: &Definition) -> bool {
+        match self {
+            Self::Table(schema, name) => {
+                *schema == definition.schema.to_lowercase() && *name == definition.name.to_lowercase()
+            }
+            Self::Name(name) => *name == definition.name.to_lowercase(),
+        }
+    }
+}
+
+/// A macro's overloads, one row each in `duckdb_functions()`
+struct MacroDefinition {
+    schema: String,
+    name: String,
+    table_macro: bool,
+    overloads: Vec<String>,
+    comment: Option<String>,
+}
+
+/// SQL recreating the object `object` and its comments. A table's script
+/// includes its indexes.
+pub fn get_ddl(conn: &Connection, object: &ObjectRef) -> Result<String, AppError> {
+    let (default_database, default_schema) = current_catalog(conn)?;
+    let database = object.database.clone().unwrap_or(default_database);
+    let schema = object.schema.clone().unwrap_or(default_schema);
+    let definitions = definitions(conn, &database, Some((&schema, &object.name)))?;
+
+    let wanted = match object.object_type {
+        CatalogObjectType::FileView => CatalogObjectType::View,
+        object_type => object_type,
+    };
+    let definition = definitions
+        .iter()
+        .find(|d| d.schema == schema && d.name == object.name && d.object_type == wanted)
+        .ok_or_else(|| AppError::invalid_input(format!("Unknown object: {}", object.name)))?;
+
+    let mut scripts = vec![definition.statements.join("\n")];
+    if wanted == CatalogObjectType::Table {
+        scripts.extend(
+            definitions
+                .iter()
+                .filter(|d| {
+                    d.object_type == CatalogObjectType::Index
+                        && d.schema == schema
+                        && d.table_name.as_deref() == Some(&object.name)
+                })
+                .map(|d| d.statements.join("\n")),
+        );
+    }
+    Ok(scripts.join("\n\n") + "\n")
+}
+
+/// A script recreating every schema, sequence, table, view, macro and index
+/// of `database`, the session's default when not given, without any data.
+///
+/// Objects come in an order that lets the script run top to bottom:
+/// sequences before the tables using them, tables after those their foreign
+/// keys reference, and views and macros after the objects they mention.
+/// DuckDB does not track what views and macros depend on, so that is read
+/// from the names in their SQL. The catalog does not keep macro parameter
+/// defaults either, so those are left out.
+pub fn dump_schema(conn: &Connection, database: Option<&str>) -> Result<String, AppError> {
+    let database = match database {
+        Some(database) => database.to_string(),
+        None => current_catalog(conn)?.0,
+    };
+
+    let mut stmt = conn.prepare(
+        "SELECT schema_name FROM duckdb_schemas()
+         WHERE database_name = ? AND NOT internal
+         ORDER BY schema_name",
+    )?;
+    let mut scripts = stmt
+        .query_map([&database], |row| row.get::<_, String>(0))?
+        .map(|schema| Ok(format!("CREATE SCHEMA {};", quote_identifier(&schema?))))
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    let mut definitions = definitions(conn, &database, None)?;
+    let rank = |object_type| match object_type {
+        CatalogObjectType::Sequence => 0,
+        CatalogObjectType::Table => 1,
+        CatalogObjectType::Index => 3,
+        _ => 2,
+    };
+    definitions.sort_by_key(|d| rank(d.object_type));
+
+    let mut start = 0;
+    while start < definitions.len() {
+        let group = rank(definitions[start].object_type);
+        let end = definitions[start..]
+            .iter()
+            .position(|d| rank(d.object_type) != group)
+            .map_or(definitions.len(), |len| start + len);
+        scripts.extend(
+            dependency_order(&definitions[start..end])
+                .into_iter()
+                .map(|d| d.statements.join("\n")),
+        );
+        start = end;
+    }
+
+    Ok(scripts.join("\n\n") + "\n")
+}
+
+/// `definitions` reordered so each comes after those it refers to, keeping
+/// the given order otherwise. Circular references are broken in that order.
+fn dependency_order(definitions: &[Definition]) -> Vec<&Definition> {
+    let dependencies: Vec<Vec<usize>> = definitions
+        .iter()
+        .enumerate()
+        .map(|(i, definition)| {
+            (0..definitions.len())
+                .filter(|&j| j != i && definition.references.iter().any(|r| r.matches(&definitions[j])))
+                .collect()
+        })
+        .collect();
+    let mut created = vec![false; definitions.len()];
+    let mut ordered = Vec::with_capacity(definitions.len());
+
+    while ordered.len() < definitions.len() {
+        let mut remaining = (0..definitions.len()).filter(|&i| !created[i]);
+        let next = remaining
+            .clone()
+            .find(|&i| dependencies[i].iter().all(|&j| created[j]))
+            .or_else(|| remaining.next());
+        let Some(next) = next else { break };
+        created[next] = true;
+        ordered.push(&definitions[next]);
+    }
+    ordered
+}
+
+/// Definitions of every sequence, table, view, macro and index of
+/// `database`, in schema and name order. With `object`, a schema and name,
+/// only the objects of that name are read, along with the indexes of a
+/// table of that name.
+fn definitions(
+    conn: &Connection,
+    database: &str,
+    object: Option<(&str, &str)>,
+) -> Result<Vec<Definition>, AppError> {
+    let mut definitions = Vec::new();
+    let (schema, name) = object.unzip();
+    let scope = params![database, schema, name];
+
+    let mut stmt = conn.prepare(
+        "SELECT schema_name, sequence_name, increment_by, min_value, max_value, start_value, cycle, comment
+         FROM duckdb_sequences()
+         WHERE database_name = $1 AND ($2::VARCHAR IS NULL OR (schema_name = $2 AND sequence_name = $3))
+         ORDER BY schema_name, sequence_name",
+    )?;
+    let mut rows = stmt.query(scope)?;
+    while let Some(row) = rows.next()? {
+        let schema: String = row.get(0)?;
+        let name: String = row.get(1)?;
+        let qualified = qualified_name(None, Some(&schema), &name);
+        let mut statements = vec![format!(
+            "CREATE SEQUENCE {} INCREMENT BY {} MINVALUE {} MAXVALUE {} START {}{};",
+            qualified,
+            row.get::<_, i64>(2)?,
+            row.get::<_, i64>(3)?,
+            row.get::<_, i64>(4)?,
+            row.get::<_, i64>(5)?,
+            if row.get(6)? { " CYCLE" } else { " NO CYCLE" }
+        )];
+        statements.extend(comment_statement("SEQUENCE", &qualified, row.get(7)?));
+        definitions.push(Definition {
+            schema,
+            name,
+            object_type: CatalogObjectType::Sequence,
+            statements,
+            references: HashSet::new(),
+            table_name: None,
+        });
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT schema_name, table_name, sql, comment FROM duckdb_tables()
+         WHERE database_name = $1 AND NOT internal
+           AND ($2::VARCHAR IS NULL OR (schema_name = $2 AND table_name = $3))
+         ORDER BY schema_name, table_name",
+    )?;
+    let mut rows = stmt.query(scope)?;
+    while let Some(row) = rows.next()? {
+        let schema: String = row.get(0)?;
+        let name: String = row.get(1)?;
+        let qualified = qualified_name(None, Some(&schema), &name);
+        let sql: String = row.get(2)?;
+        let mut statements = Vec::new();
+        statements.extend(comment_statement("TABLE", &qualified, row.get(3)?));
+
+        let mut stmt = conn.prepare(
+            "SELECT column_name, comment FROM duckdb_columns()
+             WHERE database_name = ? AND schema_name = ? AND table_name = ? AND comment IS NOT NULL
+             ORDER BY column_index",
+        )?;
+        let mut columns = stmt.query(params![database, schema, name])?;
+        while let Some(column) = columns.next()? {
+            let target = format!("{}.{}", qualified, quote_identifier(&column.get::<_, String>(0)?));
+            statements.extend(comment_statement("COLUMN", &target, column.get(1)?));
+        }
+
+        let (sql, references) = quote_foreign_keys(conn, database, &schema, &name, sql)?;
+        statements.insert(0, sql);
+
+        definitions.push(Definition {
+            schema,
+            name,
+            object_type: CatalogObjectType::Table,
+            statements,
+            references,
+            table_name: None,
+        });
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT schema_name, view_name, sql, comment FROM duckdb_views()
+         WHERE database_name = $1 AND NOT internal
+           AND ($2::VARCHAR IS NULL OR (schema_name = $2 AND view_name = $3))
+         ORDER BY schema_name, view_name",
+    )?;
+    let mut rows = stmt.query(scope)?;
+    while let Some(row) = rows.next()? {
+        let schema: String = row.get(0)?;
+        let name: String = row.get(1)?;
+        let sql: String = row.get(2)?;
+        let mut statements = vec![sql.clone()];
+        statements.extend(comment_statement(
+            "VIEW",
+            &qualified_name(None, Some(&schema), &name),
+            row.get(3)?,
+        ));
+        definitions.push(Definition {
+            schema,
+            name,
+            object_type: CatalogObjectType::View,
+            statements,
+            references: name_references(&sql),
+            table_name: None,
+        });
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT schema_name, function_name, function_type, parameters, parameter_types, macro_definition, comment
+         FROM duckdb_functions()
+         WHERE database_name = $1 AND NOT internal AND function_type IN ('macro', 'table_macro')
+           AND ($2::VARCHAR IS NULL OR (schema_name = $2 AND function_name = $3))
+         ORDER BY schema_name, function_name, function_type",
+    )?;
+    let mut rows = stmt.query(scope)?;
+    let mut macros: Vec<MacroDefinition> = Vec::new();
+    while let Some(row) = rows.next()? {
+        let schema: String = row.get(0)?;
+        let name: String = row.get(1)?;
+        let table_macro = row.get::<_, String>(2)? == "table_macro";
+        let parameters = string_list(row.get(3)?)
+            .into_iter()
+            .zip(optional_string_list(row.get(4)?))
+            .map(|(parameter, data_type)| match data_type {
+                Some(data_type) => format!("{} {}", quote_identifier(&parameter), data_type),
+                None => quote_identifier(&parameter),
+            })
+            .collect::<Vec<_>>();
+        let body: String = row.get(5)?;
+        let overload = format!(
+            "({}) AS {}{}",
+            parameters.join(", "),
+            if table_macro { "TABLE " } else { "" },
+            body
+        );
+
+        match macros.last_mut() {
+            Some(last) if last.schema == schema && last.name == name && last.table_macro == table_macro => {
+                last.overloads.push(overload)
+            }
+            _ => macros.push(MacroDefinition {
+                schema,
+                name,
+                table_macro,
+                overloads: vec![overload],
+                comment: row.get(6)?,
+            }),
+        }
+    }
+    for MacroDefinition {
+        schema,
+        name,
+        table_macro,
+        overloads,
+        comment,
+    } in macros
+    {
+        let qualified = qualified_name(None, Some(&schema), &name);
+        let mut statements = vec![format!("CREATE MACRO {}{};", qualified, overloads.join(", "))];
+        let kind = if table_macro { "MACRO TABLE" } else { "MACRO" };
+        statements.extend(comment_statement(kind, &qualified, comment));
+        let references = name_references(&statements[0]);
+        definitions.push(Definition {
+            schema,
+            name,
+            object_type: if table_macro {
+                CatalogObjectType::TableMacro
+            } else {
+                CatalogObjectType::Macro
+            },
+            statements,
+            references,
+            table_name: None,
+        });
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT schema_name, index_name, table_name, sql, comment FROM duckdb_indexes()
+         WHERE database_name = $1 AND sql IS NOT NULL
+           AND ($2::VARCHAR IS NULL OR (schema_name = $2 AND (index_name = $3 OR table_name = $3)))
+         ORDER BY schema_name, index_name",
+    )?;
+    let mut rows = stmt.query(scope)?;
+    while let Some(row) = rows.next()? {
+        let schema: String = row.get(0)?;
+        let name: String = row.get(1)?;
+        let mut statements = vec![row.get::<_, String>(3)?];
+        statements.extend(comment_statement(
+            "INDEX",
+            &qualified_name(None, Some(&schema), &name),
+            row.get(4)?,
+        ));
+        definitions.push(Definition {
+            schema,
+            name,
+            object_type: CatalogObjectType::Index,
+            statements,
+            references: HashSet::new(),
+            table_name: row.get(2)?,
+        });
+    }
+
+    Ok(definitions)
+}
+
+/// A table's CREATE statement `sql` with its foreign keys rebuilt from
+/// `duckdb_constraints()`, as DuckDB leaves the referenced table unquoted
+/// when generating the statement, and the tables they reference. Foreign
+/// keys cannot cross schemas, so every referenced table is in `schema`.
+fn quote_foreign_keys(
+    conn: &Connection,
+    database: &str,
+    schema: &str,
+    table: &str,
+    mut sql: String,
+) -> Result<(String, HashSet<Reference>), AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT constraint_text, constraint_column_names, referenced_table, referenced_column_names
+         FROM duckdb_constraints()
+         WHERE database_name = ? AND schema_name = ? AND table_name = ? AND constraint_type = 'FOREIGN KEY'
+         ORDER BY constraint_index",
+    )?;
+    let mut rows = stmt.query(params![database, schema, table])?;
+    let mut references = HashSet::new();
+
+    while let Some(row) = rows.next()? {
+        let generated: String = row.get(0)?;
+        let referenced_table: String = row.get(2)?;
+        let columns = |value| {
+            string_list(value)
+                .iter()
+                .map(|column| quote_identifier(column))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let clause = format!(
+            "FOREIGN KEY ({}) REFERENCES {}({})",
+            columns(row.get(1)?),
+            qualified_name(None, Some(schema), &referenced_table),
+            columns(row.get(3)?)
+        );
+
+        // DuckDB writes each constraint into the statement with the same text
+        // it reports in `constraint_text`, after the columns
+        if let Some(start) = sql.rfind(&generated) {
+            sql.replace_range(start..start + generated.len(), &clause);
+        }
+        references.insert(Reference::Table(schema.to_lowercase(), referenced_table.to_lowercase()));
+    }
+    Ok((sql, references))
+}
+
+fn comment_statement(kind: &str, target: &str, comment: Option<String>) -> Option<String> {
+    comment.map(|comment| format!("COMMENT ON {} {} IS {};", kind, target, quote_literal(&comment)))
+}
+
+/// The entries of a `VARCHAR[]` catalog column that may hold NULLs
+fn optional_string_list(value: Value) -> Vec<Option<String>> {
+    match value {
+        Value::List(items) => items
+            .into_iter()
+            .map(|item| match item {
+                Value::Text(text) => Some(text),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn name_references(sql: &str) -> HashSet<Reference> {
+    identifiers(sql).into_iter().map(Reference::Name).collect()
+}
+
+/// Lowercased plain and double-quoted identifiers appearing in `sql`, skipping
+/// string literals
+fn identifiers(sql: &str) -> HashSet<String> {
+    let mut identifiers = HashSet::new();
+    let mut chars = sql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' => {
+                let mut text = String::new();
+                while let Some(next) = chars.next() {
+                    if next == c {
+                        if chars.peek() != Some(&c) {
+                            break;
+                        }
+                        chars.next();
+                    }
+                    text.push(next);
+                }
+                if c == '"' {
+                    identifiers.insert(text.to_lowercase());
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_' || next == '$') {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                identifiers.insert(word.to_lowercase());
+            }
+            _ => {}
+        }
+    }
+    identifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = "CREATE SCHEMA shop;
+         CREATE SEQUENCE shop.order_ids START 100 INCREMENT BY 5;
+         CREATE TABLE shop.zones (id INTEGER PRIMARY KEY, name VARCHAR);
+         CREATE TABLE shop.\"order's\" (
+             id INTEGER DEFAULT nextval('shop.order_ids') PRIMARY KEY,
+             zone_id INTEGER REFERENCES shop.zones (id),
+             quantity INTEGER CHECK (quantity > 0)
+         );
+         CREATE TABLE shop.archive (id INTEGER REFERENCES shop.\"order's\" (id));
+         COMMENT ON TABLE shop.\"order's\" IS 'It''s the orders';
+         COMMENT ON COLUMN shop.\"order's\".quantity IS 'Units';
+         CREATE INDEX order_zone ON shop.\"order's\" (zone_id);
+         CREATE MACRO double_it(x) AS x * 2, (x, y) AS x * y;
+         CREATE VIEW a_view AS SELECT double_it(quantity) AS doubled FROM shop.\"order's\";
+         CREATE MACRO big_orders(n INTEGER) AS TABLE SELECT * FROM a_view WHERE doubled > n;
+         CREATE VIEW b_view AS SELECT * FROM big_orders(1);
+         COMMENT ON MACRO double_it IS 'Doubles';";
+
+    #[test]
+    fn test_get_ddl() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+
+        let object = |schema: &str, name: &str, object_type| ObjectRef {
+            database: None,
+            schema: Some(schema.to_string()),
+            name: name.to_string(),
+            object_type,
+        };
+
+        let table = get_ddl(&conn, &object("shop", "order's", CatalogObjectType::Table)).unwrap();
+        assert!(table.starts_with("CREATE TABLE shop.\"order's\"("));
+        assert!(table.contains("COMMENT ON TABLE \"shop\".\"order's\" IS 'It''s the orders';"));
+        assert!(table.contains("COMMENT ON COLUMN \"shop\".\"order's\".\"quantity\" IS 'Units';"));
+        assert!(table.contains("CREATE INDEX order_zone"));
+
+        let sequence = get_ddl(&conn, &object("shop", "order_ids", CatalogObjectType::Sequence)).unwrap();
+        assert!(sequence.starts_with("CREATE SEQUENCE \"shop\".\"order_ids\" INCREMENT BY 5 "));
+        assert!(sequence.contains("START 100 NO CYCLE;"));
+
+        let double_it = get_ddl(&conn, &object("main", "double_it", CatalogObjectType::Macro)).unwrap();
+        assert!(double_it.starts_with("CREATE MACRO \"main\".\"double_it\"(\"x\") AS "));
+        assert!(double_it.contains(", (\"x\", \"y\") AS "));
+
+        let missing = get_ddl(&conn, &object("main", "double_it", CatalogObjectType::View)).unwrap_err();
+        assert_eq!(missing.kind(), "invalidInput");
+    }
+
+    #[test]
+    fn test_dump_schema_recreates_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        let script = dump_schema(&conn, None).unwrap();
+
+        let position = |text: &str| script.find(text).unwrap_or_else(|| panic!("{} missing", text));
+        assert!(position("CREATE SCHEMA") < position("CREATE SEQUENCE"));
+        assert!(position("CREATE SEQUENCE") < position("CREATE TABLE shop.zones"));
+        assert!(position("CREATE TABLE shop.zones") < position("CREATE TABLE shop.\"order's\""));
+        assert!(position("CREATE TABLE shop.\"order's\"") < position("CREATE TABLE shop.archive"));
+        assert!(position("\"double_it\"") < position("CREATE VIEW a_view"));
+        assert!(position("CREATE VIEW a_view") < position("\"big_orders\""));
+        assert!(position("\"big_orders\"") < position("CREATE VIEW b_view"));
+
+        let copy = Connection::open_in_memory().unwrap();
+        copy.execute_batch(&script).unwrap();
+        assert_eq!(dump_schema(&copy, None).unwrap(), script);
+    }
+
+    #[test]
+    fn test_foreign_keys_across_schemas() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE SCHEMA \"A\";
+             CREATE SCHEMA \"B s\";
+             CREATE TABLE \"A\".parent (id INTEGER PRIMARY KEY);
+             CREATE TABLE \"B s\".parent (id INTEGER PRIMARY KEY);
+             CREATE TABLE \"B s\".\"a child\" (parent_id INTEGER REFERENCES \"B s\".parent (id));
+             CREATE TABLE \"B s\".\"Par ent\" (id INTEGER, k VARCHAR, PRIMARY KEY (id, k));
+             CREATE TABLE \"B s\".child (
+                 id INTEGER,
+                 \"K k\" VARCHAR,
+                 FOREIGN KEY (id, \"K k\") REFERENCES \"B s\".\"Par ent\" (id, k)
+             );",
+        )
+        .unwrap();
+
+        let child = get_ddl(
+            &conn,
+            &ObjectRef {
+                database: None,
+                schema: Some("B s".to_string()),
+                name: "child".to_string(),
+                object_type: CatalogObjectType::Table,
+            },
+        )
+        .unwrap();
+        assert!(child.contains("FOREIGN KEY (\"id\", \"K k\") REFERENCES \"B s\".\"Par ent\"(\"id\", \"k\")"));
+
+        // "B s"."a child" sorts before "B s".parent, and "A".parent being
+        // created first must not count as its parent
+        let script = dump_schema(&conn, None).unwrap();
+        let copy = Connection::open_in_memory().unwrap();
+        copy.execute_batch(&script).unwrap();
+        assert_eq!(dump_schema(&copy, None).unwrap(), script);
+    }
+
+    #[test]
+    fn test_identifiers() {
+        let found = identifiers("SELECT \"Odd \"\"Name\"\".x\", 'not_me' FROM main.t2");
+        let expected: HashSet<String> = ["select", "odd \"name\".x", "from", "main", "t2"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(found, expected);
+    }
+}
@@ -0,0 +1,306 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+/// Error returned by every command. Serialized for the frontend as
+/// `{ kind, message, line, column }`, where `line` and `column` (1-based)
+/// locate the offending token in the SQL when DuckDB reports it.
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error("No database selected")]
+    NoDatabase,
+    #[error("Operation timed out")]
+    Timeout,
+    #[error("Query cancelled")]
+    Cancelled,
+    /// The SQL could not be parsed
+    #[error("{message}")]
+    SqlSyntax {
+        message: String,
+        location: Option<SqlLocation>,
+    },
+    /// A column, function or type in the SQL could not be resolved
+    #[error("{message}")]
+    Binder {
+        message: String,
+        location: Option<SqlLocation>,
+    },
+    /// A table, view, schema or other catalog entry does not exist (or already does)
+    #[error("{message}")]
+    Catalog {
+        message: String,
+        location: Option<SqlLocation>,
+    },
+    /// A value could not be cast or is out of range
+    #[error("{message}")]
+    Conversion {
+        message: String,
+        location: Option<SqlLocation>,
+    },
+    /// The statement would modify a database opened read-only
+    #[error("{message}")]
+    ReadOnly { message: String },
+    /// A PRIMARY KEY, UNIQUE, NOT NULL, CHECK or FOREIGN KEY constraint was violated
+    #[error("{message}")]
+    Constraint { message: String },
+    /// Reading or writing a file failed
+    #[error("{message}")]
+    Io { message: String },
+    /// A DuckDB extension is missing or could not be installed or loaded
+    #[error("{message}")]
+    Extension { message: String },
+    /// A command argument was rejected before reaching the database
+    #[error("{message}")]
+    InvalidInput { message: String },
+    /// Any other error reported by DuckDB
+    #[error("{message}")]
+    Database { message: String },
+    /// A background task or lock failed
+    #[error("{message}")]
+    Internal { message: String },
+}
+
+/// Position of an error within the SQL text, 1-based. `column` is unknown
+/// when DuckDB elided the start of a long line and the SQL is not at hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SqlLocation {
+    pub line: usize,
+    pub column: Option<usize>,
+}
+
+impl AppError {
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::InvalidInput {
+            message: message.into(),
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::Internal {
+            message: message.into(),
+        }
+    }
+
+    /// Classify a DuckDB error raised while running `sql`, resolving its
+    /// location against the SQL text
+    pub fn from_query(error: duckdb::Error, sql: &str) -> Self {
+        Self::from_duckdb_message(error.to_string(), Some(sql))
+    }
+
+    /// Classify a DuckDB error by the error type its message starts with
+    fn from_duckdb_message(message: String, sql: Option<&str>) -> Self {
+        let error_type = message.split_once(" Error: ").map_or("", |(error_type, _)| error_type);
+        let location = || sql_location(&message, sql);
+
+        if message.contains("read-only mode") {
+            return Self::ReadOnly { message };
+        }
+
+        match error_type {
+            "Parser" | "Syntax" => Self::SqlSyntax {
+                location: location(),
+                message,
+            },
+            "Binder" => Self::Binder {
+                location: location(),
+                message,
+            },
+            "Catalog" if message.contains("is not in the catalog, but it exists in the") => {
+                Self::Extension { message }
+            }
+            "Catalog" | "Dependency" => Self::Catalog {
+                location: location(),
+                message,
+            },
+            "Conversion" | "Invalid Input" | "Out of Range" | "Mismatch Type" => Self::Conversion {
+                location: location(),
+                message,
+            },
+            "Constraint" => Self::Constraint { message },
+            "Missing Extension" | "Extension" => Self::Extension { message },
+            "IO" | "HTTP" if message.contains("xtension") => Self::Extension { message },
+            "IO" | "HTTP" | "Permission" => Self::Io { message },
+            "INTERRUPT" | "Interrupt" => Self::Cancelled,
+            _ => Self::Database { message },
+        }
+    }
+
+    /// Kind tag sent to the frontend
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::NoDatabase => "noDatabase",
+            Self::Timeout => "timeout",
+            Self::Cancelled => "cancelled",
+            Self::SqlSyntax { .. } => "sqlSyntax",
+            Self::Binder { .. } => "binder",
+            Self::Catalog { .. } => "catalog",
+            Self::Conversion { .. } => "conversion",
+            Self::ReadOnly { .. } => "readOnly",
+            Self::Constraint { .. } => "constraint",
+            Self::Io { .. } => "io",
+            Self::Extension { .. } => "extension",
+            Self::InvalidInput { .. } => "invalidInput",
+            Self::Database { .. } => "database",
+            Self::Internal { .. } => "internal",
+        }
+    }
+
+    pub fn location(&self) -> Option<SqlLocation> {
+        match self {
+            Self::SqlSyntax { location, .. }
+            | Self::Binder { location, .. }
+            | Self::Catalog { location, .. }
+            | Self::Conversion { location, .. } => *location,
+            _ => None,
+        }
+    }
+}
+
+impl From<duckdb::Error> for AppError {
+    fn from(error: duckdb::Error) -> Self {
+        Self::from_duckdb_message(error.to_string(), None)
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io {
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<csv::Error> for AppError {
+    fn from(error: csv::Error) -> Self {
+        Self::Io {
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<rust_xlsxwriter::XlsxError> for AppError {
+    fn from(error: rust_xlsxwriter::XlsxError) -> Self {
+        Self::Io {
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::internal(error.to_string())
+    }
+}
+
+impl From<tokio::task::JoinError> for AppError {
+    fn from(error: tokio::task::JoinError) -> Self {
+        Self::internal(format!("Task failed: {}", error))
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let location = self.location();
+        let mut state = serializer.serialize_struct("AppError", 4)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("line", &location.map(|l| l.line))?;
+        state.serialize_field("column", &location.and_then(|l| l.column))?;
+        state.end()
+    }
+}
+
+/// Read the location from the context DuckDB appends to an error:
+///
+/// ```text
+/// LINE 2: ...c38, c39 FORM t
+///                          ^
+/// ```
+///
+/// DuckDB shows at most about 40 characters either side of the error, marking
+/// elided text with `...`; the excerpt is then found in the SQL to recover
+/// the column.
+fn sql_location(message: &str, sql: Option<&str>) -> Option<SqlLocation> {
+    let context = &message[message.rfind("\n\nLINE ")? + 2..];
+    let mut lines = context.lines();
+    let excerpt_line = lines.next()?;
+    let caret_line = lines.next()?;
+
+    let (label, excerpt) = excerpt_line.split_once(": ")?;
+    let line: usize = label.strip_prefix("LINE ")?.parse().ok()?;
+    let caret = caret_line.chars().take_while(|&c| c == ' ').count();
+    let offset = caret.checked_sub(label.chars().count() + 2)?;
+
+    let column = match excerpt.strip_prefix("...") {
+        None => Some(offset + 1),
+        Some(rest) => {
+            let rest = rest.strip_suffix("...").unwrap_or(rest);
+            sql.and_then(|sql| excerpt_column(sql, line, rest, offset.saturating_sub(3)))
+        }
+    };
+
+    Some(SqlLocation { line, column })
+}
+
+/// Column of the character `offset` characters into `excerpt`, found on
+/// `line` of `sql`
+fn excerpt_column(sql: &str, line: usize, excerpt: &str, offset: usize) -> Option<usize> {
+    let text = sql.lines().nth(line.checked_sub(1)?)?;
+    let start = text.find(excerpt)?;
+    Some(text[..start].chars().count() + offset + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use duckdb::Connection;
+    use serde_json::json;
+
+    fn error_of(sql: &str) -> AppError {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (i INTEGER PRIMARY KEY)").unwrap();
+        let error = conn.execute_batch(sql).unwrap_err();
+        AppError::from_query(error, sql)
+    }
+
+    #[test]
+    fn test_duckdb_errors_are_classified() {
+        assert_eq!(error_of("SELECT * FORM t").kind(), "sqlSyntax");
+        assert_eq!(error_of("SELECT nope FROM t").kind(), "binder");
+        assert_eq!(error_of("SELECT * FROM missing").kind(), "catalog");
+        assert_eq!(error_of("SELECT 'x'::INTEGER").kind(), "conversion");
+        assert_eq!(error_of("INSERT INTO t VALUES (1), (1)").kind(), "constraint");
+        assert_eq!(error_of("SELECT * FROM read_csv('/nonexistent/x.csv')").kind(), "io");
+        assert_eq!(error_of("LOAD no_such_extension").kind(), "extension");
+        assert_eq!(error_of("BEGIN; BEGIN").kind(), "database");
+    }
+
+    #[test]
+    fn test_sql_location() {
+        let location = |sql| error_of(sql).location().unwrap();
+
+        assert_eq!(location("SELECT * FORM t"), SqlLocation { line: 1, column: Some(15) });
+        assert_eq!(location("SELECT 1,\n  nope FROM t"), SqlLocation { line: 2, column: Some(3) });
+
+        // DuckDB elides the start of long lines; the column is recovered from the SQL
+        let columns: Vec<String> = (0..40).map(|i| format!("c{}", i)).collect();
+        let long = format!("SELECT {} FORM t", columns.join(", "));
+        let expected = long.rfind('t').unwrap() + 1;
+        assert_eq!(location(&long), SqlLocation { line: 1, column: Some(expected) });
+
+        let conn = Connection::open_in_memory().unwrap();
+        let error = AppError::from(conn.execute_batch(&long).unwrap_err());
+        assert_eq!(error.location(), Some(SqlLocation { line: 1, column: None }));
+    }
+
+    #[test]
+    fn test_serialized_shape() {
+        assert_eq!(
+            serde_json::to_value(AppError::NoDatabase).unwrap(),
+            json!({ "kind": "noDatabase", "message": "No database selected", "line": null, "column": null })
+        );
+
+        let value = serde_json::to_value(error_of("SELECT * FORM t")).unwrap();
+        assert_eq!(value["kind"], "sqlSyntax");
+        assert_eq!((value["line"].clone(), value["column"].clone()), (json!(1), json!(15)));
+        assert!(value["message"].as_str().unwrap().starts_with("Parser Error: syntax error"));
+    }
+}
@@ -1,19 +1,19 @@
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
 
+use duckdb::Connection;
 use rust_xlsxwriter::Workbook;
 
+use crate::error::AppError;
 use crate::models::QueryResult;
+use crate::quote::quote_path;
 
-pub fn export_to_csv(result: &QueryResult, file_path: &str) -> Result<(), String> {
-    let file = File::create(file_path).map_err(|e| e.to_string())?;
+pub fn export_to_csv(result: &QueryResult, file_path: &str) -> Result<(), AppError> {
+    let file = File::create(file_path)?;
     let mut writer = csv::Writer::from_writer(file);
 
     // Write header
-    writer
-        .write_record(&result.columns)
-        .map_err(|e| e.to_string())?;
+    writer.write_record(&result.columns)?;
 
     // Write data rows
     for row in &result.rows {
@@ -25,24 +25,20 @@ pub fn export_to_csv(result: &QueryResult, file_path: &str) -> Result<(), String
                 other => other.to_string(),
             })
             .collect();
-        writer
-            .write_record(&string_row)
-            .map_err(|e| e.to_string())?;
+        writer.write_record(&string_row)?;
     }
 
-    writer.flush().map_err(|e| e.to_string())?;
+    writer.flush()?;
     Ok(())
 }
 
-pub fn export_to_excel(result: &QueryResult, file_path: &str) -> Result<(), String> {
+pub fn export_to_excel(result: &QueryResult, file_path: &str) -> Result<(), AppError> {
     let mut workbook = Workbook::new();
     let worksheet = workbook.add_worksheet();
 
     // Write header
     for (col, header) in result.columns.iter().enumerate() {
-        worksheet
-            .write_string(0, col as u16, header)
-            .map_err(|e| e.to_string())?;
+        worksheet.write_string(0, col as u16, header)?;
     }
 
     // Write data rows
@@ -53,45 +49,62 @@ pub fn export_to_excel(result: &QueryResult, file_path: &str) -> Result<(), Stri
 
             match value {
                 serde_json::Value::Null => {
-                    worksheet
-                        .write_string(row_num, col_num, "")
-                        .map_err(|e| e.to_string())?;
+                    worksheet.write_string(row_num, col_num, "")?;
                 }
                 serde_json::Value::Bool(b) => {
-                    worksheet
-                        .write_boolean(row_num, col_num, *b)
-                        .map_err(|e| e.to_string())?;
+                    worksheet.write_boolean(row_num, col_num, *b)?;
                 }
-                serde_json::Value::Number(n) => {
-                    if let Some(i) = n.as_i64() {
-                        worksheet
-                            .write_number(row_num, col_num, i as f64)
-                            .map_err(|e| e.to_string())?;
-                    } else if let Some(f) = n.as_f64() {
-                        worksheet
-                            .write_number(row_num, col_num, f)
-                            .map_err(|e| e.to_string())?;
-                    }
-                }
-                serde_json::Value::String(s) => {
-                    worksheet
-                        .write_string(row_num, col_num, s)
-                        .map_err(|e| e.to_string())?;
+                serde_json::Value::Number(_) | serde_json::Value::String(_) => {
+                    // Numbers a double cannot hold exactly are written as text
+                    let column_type = result.column_types.get(col_idx).map_or("", String::as_str);
+                    match excel_number(value, column_type) {
+                        Some(number) => worksheet.write_number(row_num, col_num, number),
+                        None => match value {
+                            serde_json::Value::String(s) => worksheet.write_string(row_num, col_num, s),
+                            other => worksheet.write_string(row_num, col_num, other.to_string()),
+                        },
+                    }?;
                 }
                 other => {
-                    worksheet
-                        .write_string(row_num, col_num, &other.to_string())
-                        .map_err(|e| e.to_string())?;
+                    worksheet.write_string(row_num, col_num, other.to_string())?;
                 }
             }
         }
     }
 
-    workbook.save(file_path).map_err(|e| e.to_string())?;
+    workbook.save(file_path)?;
     Ok(())
 }
 
-pub fn export_to_json(result: &QueryResult, file_path: &str) -> Result<(), String> {
+/// Integers up to this magnitude are exactly representable as a double
+const MAX_SAFE_INTEGER: u64 = 1 << 53;
+
+/// The value of a result cell as an Excel number, if a double holds it
+/// exactly. HUGEINT, UHUGEINT and DECIMAL values arrive as strings and are
+/// converted only when the number reads back as the same digits.
+fn excel_number(value: &serde_json::Value, column_type: &str) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                (i.unsigned_abs() <= MAX_SAFE_INTEGER).then_some(i as f64)
+            } else if let Some(u) = n.as_u64() {
+                (u <= MAX_SAFE_INTEGER).then_some(u as f64)
+            } else {
+                n.as_f64()
+            }
+        }
+        serde_json::Value::String(s)
+            if column_type.starts_with("DECIMAL") || column_type == "HUGEINT" || column_type == "UHUGEINT" =>
+        {
+            let number: f64 = s.parse().ok()?;
+            let scale = s.split_once('.').map_or(0, |(_, fraction)| fraction.len());
+            (format!("{:.*}", scale, number) == *s).then_some(number)
+        }
+        _ => None,
+    }
+}
+
+pub fn export_to_json(result: &QueryResult, file_path: &str) -> Result<(), AppError> {
     let mut records: Vec<serde_json::Map<String, serde_json::Value>> = Vec::new();
 
     for row in &result.rows {
@@ -104,26 +117,41 @@ pub fn export_to_json(result: &QueryResult, file_path: &str) -> Result<(), Strin
         records.push(record);
     }
 
-    let json = serde_json::to_string_pretty(&records).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(&records)?;
 
-    let mut file = File::create(file_path).map_err(|e| e.to_string())?;
-    file.write_all(json.as_bytes())
-        .map_err(|e| e.to_string())?;
+    let mut file = File::create(file_path)?;
+    file.write_all(json.as_bytes())?;
 
     Ok(())
 }
 
 pub fn export_to_parquet_via_duckdb(
-    db_path: &Path,
+    conn: &Connection,
     query: &str,
     file_path: &str,
-) -> Result<(), String> {
-    let conn = duckdb::Connection::open(db_path).map_err(|e| e.to_string())?;
-
+) -> Result<(), AppError> {
     // Use DuckDB's native COPY TO for Parquet export
-    let export_sql = format!("COPY ({}) TO '{}' (FORMAT PARQUET)", query, file_path);
+    let export_sql = format!("COPY ({}) TO {} (FORMAT PARQUET)", query, quote_path(file_path));
 
-    conn.execute(&export_sql, []).map_err(|e| e.to_string())?;
+    conn.execute(&export_sql, [])?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_excel_number_keeps_precision() {
+        assert_eq!(excel_number(&json!(42), "INTEGER"), Some(42.0));
+        assert_eq!(excel_number(&json!(1.5), "DOUBLE"), Some(1.5));
+        assert_eq!(excel_number(&json!(9007199254740993_i64), "BIGINT"), None);
+        assert_eq!(excel_number(&json!(u64::MAX), "UBIGINT"), None);
+        assert_eq!(excel_number(&json!("12.50"), "DECIMAL(10,2)"), Some(12.5));
+        assert_eq!(excel_number(&json!("123456789012345678.901"), "DECIMAL(38,3)"), None);
+        assert_eq!(excel_number(&json!("170141183460469231731687303715884105727"), "HUGEINT"), None);
+        assert_eq!(excel_number(&json!("12"), "VARCHAR"), None);
+    }
+}
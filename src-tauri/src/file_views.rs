@@ -0,0 +1,105 @@
+use duckdb::Connection;
+
+use crate::error::AppError;
+use crate::models::FileViewFormat;
+use crate::quote::{quote_identifier, quote_literal, quote_path};
+
+/// Start of the comment `register_file_view` puts on its views, which is how
+/// they are told apart from other views
+const FILE_VIEW_COMMENT: &str = "File view over ";
+
+/// CREATE a view named `name` over the files matching `paths`, which may
+/// contain globs (`data/*.parquet`). The files are read in place on every
+/// query, so nothing is copied into the database. A temporary view lasts for
+/// the session only; a persistent one is stored in the database file. The
+/// view is commented with the files it reads, which marks it as a file view.
+pub fn register_file_view(
+    conn: &Connection,
+    name: &str,
+    paths: &[String],
+    format: FileViewFormat,
+    temporary: bool,
+) -> Result<(), AppError> {
+    if paths.is_empty() {
+        return Err(AppError::invalid_input("A file view needs at least one path"));
+    }
+
+    let scan = match format {
+        FileViewFormat::Csv => "read_csv",
+        FileViewFormat::Parquet => "read_parquet",
+        FileViewFormat::Json => "read_json",
+    };
+    let comment = format!("{}{}", FILE_VIEW_COMMENT, paths.join(", "));
+    let paths = paths.iter().map(quote_path).collect::<Vec<_>>().join(", ");
+
+    conn.execute_batch(&format!(
+        "CREATE {}VIEW {} AS SELECT * FROM {}([{}]);
+         COMMENT ON VIEW {} IS {};",
+        if temporary { "TEMP " } else { "" },
+        quote_identifier(name),
+        scan,
+        paths,
+        quote_identifier(name),
+        quote_literal(&comment)
+    ))?;
+
+    Ok(())
+}
+
+/// Whether a view with `comment` (as in `duckdb_views().comment`) was made
+/// by `register_file_view`
+pub fn is_file_view(comment: Option<&str>) -> bool {
+    comment.is_some_and(|comment| comment.starts_with(FILE_VIEW_COMMENT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_file_view_over_glob() {
+        let dir = std::env::temp_dir().join(format!("duckdb-viewer-file-view-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        for part in 0..3 {
+            conn.execute_batch(&format!(
+                "COPY (SELECT {0} AS part, range AS n FROM range(10)) TO {1}",
+                part,
+                quote_path(dir.join(format!("part-{}.csv", part)))
+            ))
+            .unwrap();
+        }
+
+        let pattern = dir.join("part-*.csv").to_string_lossy().to_string();
+        register_file_view(&conn, "parts", std::slice::from_ref(&pattern), FileViewFormat::Csv, true).unwrap();
+
+        let (rows, parts): (i64, i64) = conn
+            .query_row("SELECT count(*), count(DISTINCT part) FROM parts", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((rows, parts), (30, 3));
+
+        conn.execute_batch(&format!("CREATE VIEW scanned AS SELECT * FROM read_csv({})", quote_path(&pattern)))
+            .unwrap();
+        let views = conn
+            .prepare("SELECT view_name, temporary, comment FROM duckdb_views() WHERE NOT internal ORDER BY view_name")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<Vec<(String, bool, Option<String>)>, _>>()
+            .unwrap();
+        let (_, temporary, comment) = &views[0];
+        assert!(temporary);
+        assert!(is_file_view(comment.as_deref()));
+        assert_eq!(comment.as_deref(), Some(format!("File view over {}", pattern).as_str()));
+        assert_eq!(views[1].0, "scanned");
+        assert!(!is_file_view(views[1].2.as_deref()));
+
+        let error = register_file_view(&conn, "empty", &[], FileViewFormat::Parquet, true).unwrap_err();
+        assert_eq!(error.kind(), "invalidInput");
+
+        drop(conn);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
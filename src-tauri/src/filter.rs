@@ -0,0 +1,227 @@
+use crate::error::AppError;
+use crate::models::{Filter, FilterCondition, FilterOp, SortSpec};
+use crate::params::{placeholder, QueryParam};
+use crate::quote::quote_identifier;
+
+/// SQL condition for `filter` with a placeholder per value, whose
+/// parameters are appended to `params` in placeholder order. Columns are
+/// quoted and values bound, so nothing from the filter is spliced into the
+/// SQL as-is. An empty AND group matches every row, an empty OR group none.
+pub fn filter_sql(filter: &Filter, params: &mut Vec<QueryParam>) -> Result<String, AppError> {
+    match filter {
+        Filter::And(filters) | Filter::Or(filters) if filters.is_empty() => {
+            Ok(matches!(filter, Filter::And(_)).to_string())
+        }
+        Filter::And(filters) => group_sql(filters, " AND ", params),
+        Filter::Or(filters) => group_sql(filters, " OR ", params),
+        Filter::Condition(condition) => condition_sql(condition, params),
+    }
+}
+
+fn group_sql(filters: &[Filter], separator: &str, params: &mut Vec<QueryParam>) -> Result<String, AppError> {
+    let conditions = filters
+        .iter()
+        .map(|filter| filter_sql(filter, params))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!("({})", conditions.join(separator)))
+}
+
+fn condition_sql(condition: &FilterCondition, params: &mut Vec<QueryParam>) -> Result<String, AppError> {
+    let column = quote_identifier(&condition.column);
+    let invalid = |expected: &str| {
+        AppError::invalid_input(format!(
+            "Filter on {} with {:?} needs {}",
+            condition.column, condition.op, expected
+        ))
+    };
+    let single = |params: &mut Vec<QueryParam>| match (&condition.value, &condition.values) {
+        (Some(value), None) => {
+            params.push(value.clone());
+            Ok(placeholder(value))
+        }
+        _ => Err(invalid("a value")),
+    };
+
+    let sql = match condition.op {
+        FilterOp::Eq => format!("{} = {}", column, single(params)?),
+        FilterOp::Ne => format!("{} <> {}", column, single(params)?),
+        FilterOp::Lt => format!("{} < {}", column, single(params)?),
+        FilterOp::Le => format!("{} <= {}", column, single(params)?),
+        FilterOp::Gt => format!("{} > {}", column, single(params)?),
+        FilterOp::Ge => format!("{} >= {}", column, single(params)?),
+        FilterOp::Like => format!("{} LIKE {}", column, single(params)?),
+        FilterOp::NotLike => format!("{} NOT LIKE {}", column, single(params)?),
+        FilterOp::Ilike => format!("{} ILIKE {}", column, single(params)?),
+        FilterOp::NotIlike => format!("{} NOT ILIKE {}", column, single(params)?),
+        FilterOp::In | FilterOp::NotIn => {
+            let values = match (&condition.value, &condition.values) {
+                (None, Some(values)) if !values.is_empty() => values,
+                _ => return Err(invalid("a non-empty list of values")),
+            };
+            params.extend(values.iter().cloned());
+            format!(
+                "{} {}IN ({})",
+                column,
+                if condition.op == FilterOp::NotIn { "NOT " } else { "" },
+                values.iter().map(placeholder).collect::<Vec<_>>().join(", ")
+            )
+        }
+        FilterOp::Between | FilterOp::NotBetween => {
+            let bounds = match (&condition.value, &condition.values) {
+                (None, Some(values)) if values.len() == 2 => values,
+                _ => return Err(invalid("two values")),
+            };
+            params.extend(bounds.iter().cloned());
+            format!(
+                "{} {}BETWEEN {} AND {}",
+                column,
+                if condition.op == FilterOp::NotBetween { "NOT " } else { "" },
+                placeholder(&bounds[0]),
+                placeholder(&bounds[1])
+            )
+        }
+        FilterOp::IsNull | FilterOp::IsNotNull => {
+            if condition.value.is_some() || condition.values.is_some() {
+                return Err(invalid("no value"));
+            }
+            format!(
+                "{} IS {}NULL",
+                column,
+                if condition.op == FilterOp::IsNotNull { "NOT " } else { "" }
+            )
+        }
+    };
+    Ok(sql)
+}
+
+/// `ORDER BY` list for `sort`, or None when it is empty
+pub fn order_by_sql(sort: &[SortSpec]) -> Option<String> {
+    if sort.is_empty() {
+        return None;
+    }
+    let keys = sort
+        .iter()
+        .map(|key| {
+            format!(
+                "{} {} NULLS {}",
+                quote_identifier(&key.column),
+                if key.descending { "DESC" } else { "ASC" },
+                if key.nulls_first { "FIRST" } else { "LAST" }
+            )
+        })
+        .collect::<Vec<_>>();
+    Some(keys.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{bind_params, QueryParams};
+    use duckdb::Connection;
+    use serde_json::json;
+
+    /// Ids of the rows of a small table matching `filter` (as the frontend
+    /// sends it), in `sort` order
+    fn matching_ids(filter: serde_json::Value, sort: serde_json::Value) -> Result<Vec<i64>, String> {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE people (id INTEGER, name VARCHAR, born DATE, score DOUBLE);
+             INSERT INTO people VALUES
+                 (1, 'Ann', '1990-05-01', 7.5),
+                 (2, 'bob', '1985-01-20', NULL),
+                 (3, 'Cy''s', NULL, 9.0),
+                 (4, 'Dee', '2001-12-31', 3.0);",
+        )
+        .unwrap();
+
+        let filter: Filter = serde_json::from_value(filter).unwrap();
+        let sort: Vec<SortSpec> = serde_json::from_value(sort).unwrap();
+        let mut params = Vec::new();
+        let condition = filter_sql(&filter, &mut params).map_err(|e| e.to_string())?;
+        let sql = format!(
+            "SELECT id FROM people WHERE {} ORDER BY {}",
+            condition,
+            order_by_sql(&sort).unwrap_or_else(|| "id".to_string())
+        );
+
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        bind_params(&mut stmt, &QueryParams::Positional(params)).map_err(|e| e.to_string())?;
+        stmt.raw_execute().map_err(|e| e.to_string())?;
+        let mut rows = stmt.raw_query();
+        let mut ids = Vec::new();
+        while let Some(row) = rows.next().unwrap() {
+            ids.push(row.get(0).unwrap());
+        }
+        Ok(ids)
+    }
+
+    fn condition(column: &str, op: &str, value: serde_json::Value) -> serde_json::Value {
+        json!({"condition": {"column": column, "op": op, "value": value}})
+    }
+
+    fn condition_with_values(column: &str, op: &str, values: serde_json::Value) -> serde_json::Value {
+        json!({"condition": {"column": column, "op": op, "values": values}})
+    }
+
+    #[test]
+    fn test_filter_conditions() {
+        let ids = |filter| matching_ids(filter, json!([])).unwrap();
+
+        assert_eq!(ids(condition("id", "ge", json!({"type": "int", "value": 3}))), vec![3, 4]);
+        assert_eq!(ids(condition("name", "eq", json!({"type": "text", "value": "Cy's"}))), vec![3]);
+        assert_eq!(ids(condition("name", "ilike", json!({"type": "text", "value": "b%"}))), vec![2]);
+        assert_eq!(ids(condition("name", "not_like", json!({"type": "text", "value": "%e%"}))), vec![1, 2, 3]);
+        assert_eq!(ids(condition("born", "lt", json!({"type": "date", "value": "1995-01-01"}))), vec![1, 2]);
+        assert_eq!(ids(condition("score", "gt", json!({"type": "decimal", "value": "7.25"}))), vec![1, 3]);
+        assert_eq!(
+            ids(condition_with_values("id", "in", json!([{"type": "int", "value": 4}, {"type": "int", "value": 1}]))),
+            vec![1, 4]
+        );
+        assert_eq!(
+            ids(condition_with_values(
+                "score",
+                "between",
+                json!([{"type": "double", "value": 3.0}, {"type": "double", "value": 8.0}])
+            )),
+            vec![1, 4]
+        );
+        assert_eq!(ids(json!({"condition": {"column": "score", "op": "is_null"}})), vec![2]);
+        assert_eq!(ids(json!({"condition": {"column": "born", "op": "is_not_null"}})), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn test_filter_groups_and_sort() {
+        let filter = json!({"or": [
+            {"and": [
+                condition("score", "gt", json!({"type": "double", "value": 5})),
+                {"condition": {"column": "born", "op": "is_not_null"}},
+            ]},
+            condition("name", "eq", json!({"type": "text", "value": "bob"})),
+        ]});
+        assert_eq!(matching_ids(filter.clone(), json!([])).unwrap(), vec![1, 2]);
+        assert_eq!(matching_ids(filter, json!([{"column": "id", "descending": true}])).unwrap(), vec![2, 1]);
+
+        assert_eq!(matching_ids(json!({"and": []}), json!([])).unwrap(), vec![1, 2, 3, 4]);
+        assert_eq!(matching_ids(json!({"or": []}), json!([])).unwrap(), Vec::<i64>::new());
+
+        let by_score = |sort| matching_ids(json!({"and": []}), sort).unwrap();
+        assert_eq!(by_score(json!([{"column": "score"}])), vec![4, 1, 3, 2]);
+        assert_eq!(by_score(json!([{"column": "score", "descending": true, "nulls_first": true}])), vec![2, 3, 1, 4]);
+    }
+
+    #[test]
+    fn test_filter_rejects_bad_input() {
+        let error = |filter| matching_ids(filter, json!([])).unwrap_err();
+
+        assert!(error(json!({"condition": {"column": "id", "op": "eq"}})).contains("needs a value"));
+        assert!(error(condition_with_values("id", "in", json!([]))).contains("non-empty list"));
+        assert!(error(condition_with_values("id", "between", json!([{"type": "int", "value": 1}]))).contains("two values"));
+
+        // Column names are quoted, never spliced in
+        let hostile = condition("id\" = 1 OR \"id", "eq", json!({"type": "int", "value": 1}));
+        assert!(error(hostile).contains("not found"));
+        // Values are bound, never spliced in
+        let injected = condition("name", "eq", json!({"type": "text", "value": "x' OR '1'='1"}));
+        assert_eq!(matching_ids(injected, json!([])).unwrap(), Vec::<i64>::new());
+    }
+}
@@ -1,9 +1,28 @@
+mod blob;
+mod catalog;
 mod commands;
+mod cursor;
+mod databases;
 mod db;
+mod ddl;
+mod error;
 mod export;
+mod file_views;
+mod filter;
 mod models;
+mod params;
+mod profile;
+mod quote;
+mod script;
+mod settings;
+mod table_schema;
+mod tables;
+mod types;
+mod values;
 
 use db::DatabaseManager;
+use settings::SettingsManager;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -14,19 +33,42 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             commands::open_database,
             commands::create_database,
+            commands::open_memory_database,
+            commands::persist_database,
             commands::close_database,
             commands::get_current_database,
+            commands::list_databases,
+            commands::attach_database,
+            commands::attach_source,
+            commands::detach_database,
+            commands::use_database,
             commands::get_tables,
+            commands::get_catalog,
+            commands::register_file_view,
             commands::get_table_schema,
+            commands::profile_table,
+            commands::get_ddl,
+            commands::dump_schema,
             commands::get_table_data,
+            commands::get_cell_blob,
+            commands::save_blob,
             commands::execute_query,
+            commands::execute_script,
+            commands::cancel_query,
+            commands::open_query,
+            commands::fetch_rows,
+            commands::close_cursor,
             commands::export_data,
             commands::import_csv,
             commands::import_parquet,
             commands::import_excel,
             commands::create_sample_data,
+            commands::get_settings,
+            commands::update_settings,
         ])
         .setup(|app| {
+            app.manage(SettingsManager::load(app.handle()));
+
             if cfg!(debug_assertions) {
                 app.handle().plugin(
                     tauri_plugin_log::Builder::default()
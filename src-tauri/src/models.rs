@@ -1,15 +1,142 @@
 use serde::{Deserialize, Serialize};
 
+use crate::params::QueryParam;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseInfo {
     pub path: String,
     pub tables: Vec<String>,
+    pub access_mode: AccessMode,
+}
+
+/// How a database file is opened. DuckDB lets one process open a file
+/// read-write or any number of processes open it read-only, never both: a
+/// read-only session can share the file with other readers but fails to open
+/// while another process writes to it, and blocks writers while it is open.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessMode {
+    ReadOnly,
+    #[default]
+    ReadWrite,
+}
+
+/// A database of the session: the opened file or one ATTACHed to it.
+/// `source_type` is DuckDB's storage type (`duckdb`, `sqlite`, `postgres`,
+/// ...); `is_default` marks the one unqualified table names resolve against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachedDatabase {
+    pub name: String,
+    pub path: Option<String>,
+    pub source_type: String,
+    pub access_mode: AccessMode,
+    pub is_default: bool,
+}
+
+/// Kinds of external database `attach_source` can attach through DuckDB's
+/// scanner extensions. SQLite and DuckDB sources are files; Postgres and
+/// MySQL sources are libpq/MySQL connection strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceType {
+    Duckdb,
+    Sqlite,
+    Postgres,
+    Mysql,
+}
+
+/// The open database reported by `get_current_database`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrentDatabase {
+    pub path: String,
+    pub access_mode: AccessMode,
+    /// A scratch database opened with `open_memory_database`
+    pub in_memory: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableInfo {
     pub name: String,
-    pub row_count: i64,
+    pub database: String,
+    pub schema: String,
+    pub table_type: TableType,
+    /// DuckDB's estimate unless `row_count_exact`; None for views, which
+    /// would have to be run to count them, and when counting failed
+    pub row_count: Option<i64>,
+    pub row_count_exact: bool,
+    /// Why the exact count of this table failed
+    pub row_count_error: Option<String>,
+    /// Bytes of database-file blocks holding the table; None unless asked
+    /// for, for views and for data that is not (yet) stored in a DuckDB file
+    pub size_bytes: Option<i64>,
+}
+
+/// What a `TableInfo` lists: a base table, a view, or a view reading files
+/// in place (see `register_file_view`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableType {
+    Table,
+    View,
+    FileView,
+}
+
+/// A database in the tree returned by `get_catalog`; `temp` holds the
+/// session's temporary objects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogDatabase {
+    pub name: String,
+    pub path: Option<String>,
+    pub is_default: bool,
+    pub schemas: Vec<CatalogSchema>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogSchema {
+    pub name: String,
+    pub comment: Option<String>,
+    pub objects: Vec<CatalogObject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogObject {
+    pub name: String,
+    pub object_type: CatalogObjectType,
+    pub comment: Option<String>,
+    pub temporary: bool,
+    /// The indexed table, for indexes
+    pub table_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogObjectType {
+    Table,
+    View,
+    FileView,
+    Sequence,
+    Macro,
+    TableMacro,
+    Index,
+}
+
+/// A catalog object to generate DDL for, looked up in the session's default
+/// database and schema unless given
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectRef {
+    pub database: Option<String>,
+    pub schema: Option<String>,
+    pub name: String,
+    pub object_type: CatalogObjectType,
+}
+
+/// File formats `register_file_view` can read in place
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileViewFormat {
+    Csv,
+    Parquet,
+    Json,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,12 +146,111 @@ pub struct ColumnInfo {
     pub nullable: bool,
     pub default_value: Option<String>,
     pub is_primary_key: bool,
+    pub comment: Option<String>,
+    /// Expression of a `GENERATED ALWAYS AS` column, when DuckDB reports the
+    /// column as generated
+    pub generated_expression: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableSchema {
     pub table_name: String,
+    pub database: String,
+    pub schema: String,
+    pub comment: Option<String>,
     pub columns: Vec<ColumnInfo>,
+    pub unique_constraints: Vec<UniqueConstraint>,
+    pub check_constraints: Vec<CheckConstraint>,
+    pub foreign_keys: Vec<ForeignKey>,
+    pub indexes: Vec<IndexInfo>,
+}
+
+/// What `profile_table` looks at: only `columns` when given, and a random
+/// `sample` of that many rows instead of every row
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileOptions {
+    pub columns: Option<Vec<String>>,
+    pub sample: Option<u64>,
+}
+
+/// Column statistics returned by `profile_table`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableProfile {
+    /// Rows profiled: the sample size when `sampled`
+    pub row_count: i64,
+    pub sampled: bool,
+    pub columns: Vec<ColumnProfile>,
+}
+
+/// Statistics of one column. Min and max are rendered as text so any
+/// ordered type fits; mean, standard deviation, quantiles and the histogram
+/// are only computed for numeric columns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnProfile {
+    pub name: String,
+    pub data_type: String,
+    pub null_count: i64,
+    pub null_percent: f64,
+    pub distinct_count: i64,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub mean: Option<f64>,
+    pub stddev: Option<f64>,
+    pub quantiles: Vec<QuantileValue>,
+    /// Most frequent values, NULL included, most frequent first
+    pub top_values: Vec<ValueCount>,
+    pub histogram: Vec<HistogramBin>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantileValue {
+    pub quantile: f64,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueCount {
+    pub value: Option<String>,
+    pub count: i64,
+}
+
+/// Count of values in `[lower, upper)`; the last bin includes `upper`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistogramBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniqueConstraint {
+    pub name: String,
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckConstraint {
+    pub name: String,
+    pub expression: Option<String>,
+}
+
+/// A foreign key; DuckDB only lets it reference a table in the same schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForeignKey {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub referenced_table: Option<String>,
+    pub referenced_columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexInfo {
+    pub name: String,
+    pub is_unique: bool,
+    /// Indexed columns and expressions as DuckDB lists them, e.g. `[a, '(b + 1)']`
+    pub expressions: Option<String>,
+    /// The CREATE INDEX statement
+    pub sql: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +262,150 @@ pub struct QueryResult {
     pub execution_time_ms: u128,
 }
 
+/// A row filter: a single condition or an AND/OR group of filters, e.g.
+/// `{"and": [{"condition": {"column": "city", "op": "eq", "value": {"type": "text", "value": "Tokyo"}}}]}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Filter {
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Condition(FilterCondition),
+}
+
+/// `column op value`. Comparisons and LIKE patterns take `value`, IN and
+/// BETWEEN take `values` (the two bounds for BETWEEN), the NULL checks neither.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterCondition {
+    pub column: String,
+    pub op: FilterOp,
+    pub value: Option<QueryParam>,
+    pub values: Option<Vec<QueryParam>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Like,
+    NotLike,
+    Ilike,
+    NotIlike,
+    In,
+    NotIn,
+    Between,
+    NotBetween,
+    IsNull,
+    IsNotNull,
+}
+
+/// One `ORDER BY` key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortSpec {
+    pub column: String,
+    #[serde(default)]
+    pub descending: bool,
+    /// NULLs sort last unless set
+    #[serde(default)]
+    pub nulls_first: bool,
+}
+
+/// Ordering and filtering `get_table_data` applies before paging
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableDataOptions {
+    #[serde(default)]
+    pub sort: Vec<SortSpec>,
+    pub filter: Option<Filter>,
+}
+
+/// A page of `get_table_data`, with the number of rows matching its filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TablePage {
+    #[serde(flatten)]
+    pub result: QueryResult,
+    pub total_rows: i64,
+    /// `rowid` of each row, to pass to `get_cell_blob` and `save_blob`;
+    /// None for views, whose rows have none
+    pub rowids: Option<Vec<i64>>,
+}
+
+/// A BLOB cell read by `get_cell_blob`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlobData {
+    pub size: usize,
+    /// Sniffed from the content, `application/octet-stream` if unknown
+    pub mime_type: String,
+    pub base64: String,
+    /// `hexdump -C` style dump of the first bytes
+    pub hex_preview: String,
+}
+
+/// How `execute_query` and `open_query` return numeric values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultEncoding {
+    /// Plain JSON numbers; HUGEINT, UHUGEINT and DECIMAL as strings. BIGINT
+    /// and UBIGINT values beyond 2^53 lose precision in JavaScript.
+    #[default]
+    Plain,
+    /// Every number as `{"type": "BIGINT", "value": "42"}`, tagged with its
+    /// DuckDB type. 64-bit and wider integers, DECIMALs and non-finite floats
+    /// are exact strings; smaller integers and floats stay JSON numbers.
+    Typed,
+}
+
+/// Outcome of one statement run by `execute_script`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatementResult {
+    pub index: usize,
+    pub sql: String,
+    /// Rows returned by the statement; `None` for DDL, DML and settings
+    pub result: Option<QueryResult>,
+    /// Rows inserted, updated, deleted or copied, when DuckDB reports a count
+    pub rows_affected: Option<i64>,
+    pub execution_time_ms: u128,
+}
+
+/// The statement a script stopped at; `line` and `column` (1-based) locate
+/// the error in the script, or the statement's start when DuckDB gives no position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptError {
+    pub statement_index: usize,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// Outcomes of the statements of a script that ran, and the error that
+/// stopped it, if any
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptResult {
+    pub statements: Vec<StatementResult>,
+    pub error: Option<ScriptError>,
+    pub execution_time_ms: u128,
+}
+
+/// A server-side cursor opened by `open_query`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorInfo {
+    pub cursor_id: String,
+    pub columns: Vec<String>,
+    pub column_types: Vec<String>,
+}
+
+/// The next batch of rows read from a cursor; `done` is set once the result
+/// is exhausted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowBatch {
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub row_count: usize,
+    pub done: bool,
+}
+
 /// Export options for batch export operations.
 /// Future use: Unified export API that accepts options as a single struct
 /// instead of individual parameters, enabling features like:
@@ -0,0 +1,362 @@
+use std::collections::BTreeMap;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use duckdb::types::{TimeUnit, Value};
+use duckdb::Statement;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::error::AppError;
+
+/// Declared type of a bind parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamType {
+    Null,
+    Bool,
+    Int,
+    Double,
+    Decimal,
+    Text,
+    Date,
+    Timestamp,
+    List,
+}
+
+/// A typed bind parameter, e.g. `{"type": "date", "value": "2024-01-31"}`.
+/// DECIMAL values are strings, LIST values arrays of parameters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryParam {
+    #[serde(rename = "type")]
+    pub param_type: ParamType,
+    #[serde(default)]
+    pub value: JsonValue,
+}
+
+/// Values for the `?`/`$1` placeholders of a statement, in order, or for its
+/// `$name` placeholders by name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum QueryParams {
+    Positional(Vec<QueryParam>),
+    Named(BTreeMap<String, QueryParam>),
+}
+
+impl Default for QueryParams {
+    fn default() -> Self {
+        Self::Positional(Vec::new())
+    }
+}
+
+/// Bind `params` to the placeholders of `stmt`. Every problem is collected so
+/// the error lists all unbound, unknown and mistyped parameters at once.
+///
+/// INT, DOUBLE, BOOL, TEXT and TIMESTAMP values are bound natively. DATE,
+/// DECIMAL and LIST values are validated here and bound as their canonical
+/// text, as duckdb-rs cannot bind those types, which DuckDB casts to the
+/// type the statement expects. Where nothing implies a type the text would
+/// compare as VARCHAR, so SQL built around a parameter uses [`placeholder`]
+/// and hand-written SQL casts it (`?::DATE`).
+pub fn bind_params(stmt: &mut Statement<'_>, params: &QueryParams) -> Result<(), AppError> {
+    let placeholders = (1..=stmt.parameter_count())
+        .map(|i| stmt.parameter_name(i))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut unbound = Vec::new();
+    let mut invalid = Vec::new();
+    let mut values = Vec::new();
+
+    for (i, name) in placeholders.iter().enumerate() {
+        let param = match params {
+            QueryParams::Positional(list) => list.get(i),
+            QueryParams::Named(map) => map
+                .iter()
+                .find(|(key, _)| key.trim_start_matches('$') == name)
+                .map(|(_, param)| param),
+        };
+        match param.map(param_value) {
+            None => unbound.push(format!("${}", name)),
+            Some(Err(e)) => invalid.push(format!("${}: {}", name, e)),
+            Some(Ok(value)) => values.push((i + 1, value)),
+        }
+    }
+
+    let unknown: Vec<String> = match params {
+        QueryParams::Positional(list) => (placeholders.len()..list.len())
+            .map(|i| format!("#{}", i + 1))
+            .collect(),
+        QueryParams::Named(map) => map
+            .keys()
+            .filter(|key| !placeholders.iter().any(|name| key.trim_start_matches('$') == name))
+            .map(|key| format!("${}", key.trim_start_matches('$')))
+            .collect(),
+    };
+
+    let mut problems = Vec::new();
+    if !unbound.is_empty() {
+        problems.push(format!("unbound {}", unbound.join(", ")));
+    }
+    if !unknown.is_empty() {
+        problems.push(format!("unknown {}", unknown.join(", ")));
+    }
+    problems.extend(invalid);
+    if !problems.is_empty() {
+        return Err(AppError::invalid_input(format!(
+            "Invalid query parameters: {}",
+            problems.join("; ")
+        )));
+    }
+
+    for (index, value) in values {
+        stmt.raw_bind_parameter(index, value)?;
+    }
+    Ok(())
+}
+
+/// `?` for `param`, cast to its type when it is bound as text (see
+/// [`bind_params`]), e.g. `CAST(? AS DECIMAL(38, 2))` for `"12.50"`
+pub fn placeholder(param: &QueryParam) -> String {
+    match cast_type(param) {
+        Some(sql_type) => format!("CAST(? AS {})", sql_type),
+        None => "?".to_string(),
+    }
+}
+
+/// SQL type a DATE, DECIMAL or LIST parameter is cast to. A DECIMAL keeps
+/// the scale it is written with; a LIST takes the type its non-NULL elements
+/// share and is left uncast when they differ.
+fn cast_type(param: &QueryParam) -> Option<String> {
+    match (param.param_type, &param.value) {
+        (ParamType::Date, _) => Some("DATE".to_string()),
+        (ParamType::Decimal, value) => {
+            let text = match value {
+                JsonValue::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            let scale = text.split_once('.').map_or(0, |(_, fraction)| fraction.len());
+            Some(format!("DECIMAL(38, {})", scale.min(38)))
+        }
+        (ParamType::List, JsonValue::Array(items)) => {
+            let mut element_types = items
+                .iter()
+                .filter_map(|item| serde_json::from_value::<QueryParam>(item.clone()).ok())
+                .filter(|item| item.param_type != ParamType::Null)
+                .map(|item| element_type(&item));
+            let first = element_types.next()??;
+            element_types
+                .all(|other| other.as_ref() == Some(&first))
+                .then(|| format!("{}[]", first))
+        }
+        _ => None,
+    }
+}
+
+fn element_type(param: &QueryParam) -> Option<String> {
+    match param.param_type {
+        ParamType::Bool => Some("BOOLEAN".to_string()),
+        ParamType::Int => Some("BIGINT".to_string()),
+        ParamType::Double => Some("DOUBLE".to_string()),
+        ParamType::Text => Some("VARCHAR".to_string()),
+        ParamType::Timestamp => Some("TIMESTAMP".to_string()),
+        _ => cast_type(param),
+    }
+}
+
+/// Check `param` against its declared type and convert it for binding
+fn param_value(param: &QueryParam) -> Result<Value, String> {
+    let mistyped = || format!("expected {}, got {}", type_name(param.param_type), param.value);
+
+    let value = match (param.param_type, &param.value) {
+        (ParamType::Null, JsonValue::Null) => Value::Null,
+        (ParamType::Bool, JsonValue::Bool(b)) => Value::Boolean(*b),
+        (ParamType::Int, JsonValue::Number(n)) => Value::BigInt(n.as_i64().ok_or_else(mistyped)?),
+        (ParamType::Int, JsonValue::String(s)) => match s.parse::<i64>() {
+            Ok(i) => Value::BigInt(i),
+            Err(_) => Value::HugeInt(s.parse().map_err(|_| mistyped())?),
+        },
+        (ParamType::Double, JsonValue::Number(n)) => Value::Double(n.as_f64().ok_or_else(mistyped)?),
+        (ParamType::Text, JsonValue::String(s)) => Value::Text(s.clone()),
+        (ParamType::Timestamp, JsonValue::String(s)) => {
+            let timestamp = parse_timestamp(s).ok_or_else(mistyped)?;
+            Value::Timestamp(TimeUnit::Microsecond, timestamp.and_utc().timestamp_micros())
+        }
+        (ParamType::Decimal | ParamType::Date | ParamType::List, _) => {
+            Value::Text(literal_text(param).ok_or_else(mistyped)?)
+        }
+        _ => return Err(mistyped()),
+    };
+    Ok(value)
+}
+
+/// Canonical text of a DECIMAL, DATE or LIST parameter as DuckDB casts it
+/// from VARCHAR, or of a list element inside `[...]`
+fn literal_text(param: &QueryParam) -> Option<String> {
+    match (param.param_type, &param.value) {
+        (ParamType::Null, JsonValue::Null) => Some("NULL".to_string()),
+        (ParamType::Bool, JsonValue::Bool(b)) => Some(b.to_string()),
+        (ParamType::Int, JsonValue::Number(n)) if n.is_i64() || n.is_u64() => Some(n.to_string()),
+        (ParamType::Int, JsonValue::String(s)) => s.parse::<i128>().ok().map(|i| i.to_string()),
+        (ParamType::Double, JsonValue::Number(n)) => Some(n.to_string()),
+        (ParamType::Decimal, JsonValue::String(s)) if is_decimal(s) => Some(s.clone()),
+        (ParamType::Decimal, JsonValue::Number(n)) => Some(n.to_string()),
+        (ParamType::Text, JsonValue::String(s)) => Some(quote_element(s)),
+        (ParamType::Date, JsonValue::String(s)) => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .ok()
+            .map(|date| date.format("%Y-%m-%d").to_string()),
+        (ParamType::Timestamp, JsonValue::String(s)) => parse_timestamp(s)
+            .map(|timestamp| quote_element(&timestamp.format("%Y-%m-%d %H:%M:%S%.f").to_string())),
+        (ParamType::List, JsonValue::Array(items)) => {
+            let elements = items
+                .iter()
+                .map(|item| serde_json::from_value::<QueryParam>(item.clone()).ok())
+                .map(|item| item.and_then(|item| literal_text(&item)))
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("[{}]", elements.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+fn parse_timestamp(s: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// An optionally signed number with an optional fractional part
+fn is_decimal(s: &str) -> bool {
+    let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    !(whole.is_empty() && fraction.is_empty())
+        && whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+}
+
+/// Quote a string element of a LIST literal
+fn quote_element(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn type_name(param_type: ParamType) -> &'static str {
+    match param_type {
+        ParamType::Null => "null",
+        ParamType::Bool => "bool",
+        ParamType::Int => "int",
+        ParamType::Double => "double",
+        ParamType::Decimal => "decimal",
+        ParamType::Text => "text",
+        ParamType::Date => "date",
+        ParamType::Timestamp => "timestamp",
+        ParamType::List => "list",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::values::value_ref_to_json;
+    use duckdb::Connection;
+    use serde_json::json;
+
+    /// Run `sql` with `params` (as the frontend sends them) and return the
+    /// first row rendered as text
+    fn query_with(sql: &str, params: JsonValue) -> Result<Vec<String>, String> {
+        let conn = Connection::open_in_memory().unwrap();
+        let params: QueryParams = serde_json::from_value(params).unwrap();
+        let mut stmt = conn.prepare(sql).unwrap();
+        bind_params(&mut stmt, &params).map_err(|e| e.to_string())?;
+        stmt.raw_execute().unwrap();
+
+        let mut rows = stmt.raw_query();
+        let count = rows.as_ref().unwrap().column_count();
+        let row = rows.next().unwrap().unwrap();
+        Ok((0..count)
+            .map(|i| match value_ref_to_json(row.get_ref(i).unwrap()) {
+                JsonValue::String(s) => s,
+                JsonValue::Null => "NULL".to_string(),
+                other => other.to_string(),
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_positional_params() {
+        let row = query_with(
+            "SELECT ? + 1, ?::DECIMAL(10,2), ?::DATE, ?, ?, ?",
+            json!([
+                { "type": "int", "value": 41 },
+                { "type": "decimal", "value": "12.345" },
+                { "type": "date", "value": "1999-12-31" },
+                { "type": "timestamp", "value": "2024-01-02T03:04:05.5" },
+                { "type": "text", "value": "it's" },
+                { "type": "null" }
+            ]),
+        )
+        .unwrap();
+        assert_eq!(
+            row,
+            vec!["42", "12.35", "1999-12-31", "2024-01-02 03:04:05.500", "it's", "NULL"]
+        );
+    }
+
+    #[test]
+    fn test_named_params_and_lists() {
+        let row = query_with(
+            "SELECT $n * 2, list_sum($ids::INTEGER[]), $names::VARCHAR[], $n",
+            json!({
+                "n": { "type": "int", "value": "21" },
+                "$ids": { "type": "list", "value": [{ "type": "int", "value": 1 }, { "type": "int", "value": 2 }] },
+                "names": { "type": "list", "value": [{ "type": "text", "value": "a, b" }, { "type": "text", "value": "it's" }] }
+            }),
+        )
+        .unwrap();
+        assert_eq!(row, vec!["42", "3", r#"["a, b","it's"]"#, "21"]);
+    }
+
+    #[test]
+    fn test_placeholders_cast_text_bound_params() {
+        let params = json!([
+            { "type": "decimal", "value": "10.5" },
+            { "type": "decimal", "value": "9.25" },
+            { "type": "list", "value": [{ "type": "int", "value": 10 }, { "type": "int", "value": 9 }] }
+        ]);
+        let typed: Vec<QueryParam> = serde_json::from_value(params.clone()).unwrap();
+        assert_eq!(placeholder(&typed[0]), "CAST(? AS DECIMAL(38, 1))");
+        assert_eq!(placeholder(&typed[2]), "CAST(? AS BIGINT[])");
+
+        // As text '10.5' sorts before '9.25'
+        let sql = format!(
+            "SELECT ? > ?, {} > {}, list_max({})",
+            placeholder(&typed[0]),
+            placeholder(&typed[1]),
+            placeholder(&typed[2])
+        );
+        let both = json!([params[0], params[1], params[0], params[1], params[2]]);
+        assert_eq!(query_with(&sql, both).unwrap(), vec!["false", "true", "10"]);
+    }
+
+    #[test]
+    fn test_param_errors_are_listed() {
+        let err = query_with(
+            "SELECT $a, $b, $c, $d",
+            json!({
+                "a": { "type": "date", "value": "2024-13-01" },
+                "b": { "type": "int", "value": "abc" },
+                "x": { "type": "int", "value": 1 }
+            }),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            "Invalid query parameters: unbound $c, $d; unknown $x; \
+             $a: expected date, got \"2024-13-01\"; $b: expected int, got \"abc\""
+        );
+
+        let err = query_with("SELECT ?", json!([])).unwrap_err();
+        assert_eq!(err, "Invalid query parameters: unbound $1");
+    }
+}
@@ -0,0 +1,391 @@
+use duckdb::types::Value;
+use duckdb::Connection;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{ColumnProfile, HistogramBin, QuantileValue, TableProfile, ValueCount};
+use crate::quote::quote_identifier;
+
+/// Most frequent values reported per column
+const TOP_VALUES: usize = 10;
+
+/// Equal-width bins of a numeric column's histogram
+const HISTOGRAM_BINS: usize = 20;
+
+/// Quantiles reported for numeric columns
+const QUANTILES: [f64; 3] = [0.25, 0.5, 0.75];
+
+const NUMERIC_TYPES: &[&str] = &[
+    "TINYINT", "SMALLINT", "INTEGER", "BIGINT", "HUGEINT", "UTINYINT", "USMALLINT", "UINTEGER", "UBIGINT",
+    "UHUGEINT", "FLOAT", "DOUBLE",
+];
+
+/// Per-column statistics of `relation`, a table reference or parenthesised
+/// query. `columns` limits the profile to those columns; `sample` profiles a
+/// random sample of that many rows instead of every row.
+///
+/// Query results and samples are materialised into a temporary table first,
+/// so every statistic describes the same rows and the query runs only once.
+pub fn profile(
+    conn: &Connection,
+    relation: &str,
+    columns: Option<&[String]>,
+    sample: Option<u64>,
+    materialize: bool,
+) -> Result<TableProfile, AppError> {
+    let source = match sample {
+        Some(rows) => format!("(SELECT * FROM {} USING SAMPLE {} ROWS)", relation, rows),
+        None => relation.to_string(),
+    };
+
+    if !materialize && sample.is_none() {
+        return profile_relation(conn, &source, columns, false);
+    }
+
+    let scratch = quote_identifier(&format!("profile_{}", Uuid::new_v4().simple()));
+    conn.execute_batch(&format!("CREATE TEMP TABLE {} AS SELECT * FROM {}", scratch, source))?;
+    let result = profile_relation(conn, &scratch, columns, sample.is_some());
+    conn.execute_batch(&format!("DROP TABLE IF EXISTS temp.main.{}", scratch))?;
+    result
+}
+
+fn profile_relation(
+    conn: &Connection,
+    relation: &str,
+    columns: Option<&[String]>,
+    sampled: bool,
+) -> Result<TableProfile, AppError> {
+    let mut stmt = conn.prepare(&format!("DESCRIBE SELECT * FROM {}", relation))?;
+    let mut described = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Some(columns) = columns {
+        if let Some(unknown) = columns.iter().find(|c| !described.iter().any(|(name, _)| name == *c)) {
+            return Err(AppError::invalid_input(format!("Unknown column: {}", unknown)));
+        }
+        described.retain(|(name, _)| columns.contains(name));
+    }
+
+    let (row_count, stats) = column_stats(conn, relation, &described)?;
+    let ranges: Vec<_> = described
+        .iter()
+        .zip(&stats)
+        .filter_map(|((name, _), stats)| stats.range.map(|range| (quote_identifier(name), range)))
+        .collect();
+    let mut histograms = histograms(conn, relation, &ranges)?.into_iter();
+    let quoted: Vec<_> = described.iter().map(|(name, _)| quote_identifier(name)).collect();
+    let top_values = top_values(conn, relation, &quoted)?;
+
+    let columns = described
+        .into_iter()
+        .zip(stats)
+        .zip(top_values)
+        .map(|(((name, data_type), stats), top_values)| {
+            let histogram = match stats.range {
+                Some(_) => histograms.next().unwrap_or_default(),
+                None => Vec::new(),
+            };
+            let null_count = row_count - stats.non_null;
+            Ok(ColumnProfile {
+                name,
+                data_type,
+                null_count,
+                null_percent: if row_count > 0 {
+                    null_count as f64 * 100.0 / row_count as f64
+                } else {
+                    0.0
+                },
+                distinct_count: stats.distinct_count,
+                min: stats.min,
+                max: stats.max,
+                mean: stats.mean,
+                stddev: stats.stddev,
+                quantiles: stats.quantiles,
+                top_values,
+                histogram,
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    Ok(TableProfile {
+        row_count,
+        sampled,
+        columns,
+    })
+}
+
+/// What the aggregate pass over the relation found out about one column
+struct ColumnStats {
+    non_null: i64,
+    distinct_count: i64,
+    min: Option<String>,
+    max: Option<String>,
+    mean: Option<f64>,
+    stddev: Option<f64>,
+    quantiles: Vec<QuantileValue>,
+    /// Finite minimum and maximum of a numeric column, for its histogram
+    range: Option<(f64, f64)>,
+}
+
+/// Row count and the aggregates of every column of `columns` (name and type),
+/// computed in a single scan of `relation`
+fn column_stats(
+    conn: &Connection,
+    relation: &str,
+    columns: &[(String, String)],
+) -> Result<(i64, Vec<ColumnStats>), AppError> {
+    let mut aggregates = vec!["count(*)".to_string()];
+    for (name, data_type) in columns {
+        let column = quote_identifier(name);
+        let ordered = !is_nested(data_type) && data_type != "BLOB";
+        aggregates.push(format!("count({})", column));
+        aggregates.push(format!("count(DISTINCT {})", column));
+        for function in ["min", "max"] {
+            aggregates.push(match ordered {
+                true => format!("{}({})::VARCHAR", function, column),
+                false => "NULL".to_string(),
+            });
+        }
+        if is_numeric(data_type) {
+            aggregates.push(format!("avg({})::DOUBLE", column));
+            aggregates.push(format!("stddev_samp({})::DOUBLE", column));
+            aggregates.extend(QUANTILES.iter().map(|q| format!("quantile_cont({}, {})::DOUBLE", column, q)));
+            let finite = format!("FILTER (WHERE isfinite({}::DOUBLE))", column);
+            aggregates.push(format!("min({}::DOUBLE) {}", column, finite));
+            aggregates.push(format!("max({}::DOUBLE) {}", column, finite));
+        }
+    }
+
+    let sql = format!("SELECT {} FROM {}", aggregates.join(", "), relation);
+    Ok(conn.query_row(&sql, [], |row| {
+        let mut index = 0;
+        let mut next = || {
+            index += 1;
+            index
+        };
+        let row_count = row.get(0)?;
+
+        let mut stats = Vec::with_capacity(columns.len());
+        for (_, data_type) in columns {
+            let mut column = ColumnStats {
+                non_null: row.get(next())?,
+                distinct_count: row.get(next())?,
+                min: row.get(next())?,
+                max: row.get(next())?,
+                mean: None,
+                stddev: None,
+                quantiles: Vec::new(),
+                range: None,
+            };
+            if is_numeric(data_type) {
+                column.mean = row.get(next())?;
+                column.stddev = row.get(next())?;
+                for &quantile in &QUANTILES {
+                    if let Some(value) = row.get::<_, Option<f64>>(next())? {
+                        column.quantiles.push(QuantileValue { quantile, value });
+                    }
+                }
+                let (low, high): (Option<f64>, Option<f64>) = (row.get(next())?, row.get(next())?);
+                column.range = low.zip(high);
+            }
+            stats.push(column);
+        }
+        Ok((row_count, stats))
+    })?)
+}
+
+/// The most frequent values of each of `columns`, most frequent first,
+/// counted in one scan of `relation`: every row is unpivoted into one
+/// (column, value) pair per column and the pairs are grouped together
+fn top_values(conn: &Connection, relation: &str, columns: &[String]) -> Result<Vec<Vec<ValueCount>>, AppError> {
+    let mut top_values = vec![Vec::new(); columns.len()];
+    if columns.is_empty() {
+        return Ok(top_values);
+    }
+
+    let pairs = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| format!("{{'column': {}, 'value': {}::VARCHAR}}", i, column))
+        .collect::<Vec<_>>();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT pair.column, pair.value, count(*) AS n
+         FROM (SELECT unnest([{0}]) AS pair FROM {1})
+         GROUP BY pair.column, pair.value
+         QUALIFY row_number() OVER (PARTITION BY pair.column ORDER BY n DESC, pair.value NULLS LAST) <= {2}
+         ORDER BY pair.column, n DESC, pair.value NULLS LAST",
+        pairs.join(", "),
+        relation,
+        TOP_VALUES
+    ))?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let column: usize = row.get(0)?;
+        top_values[column].push(ValueCount {
+            value: row.get(1)?,
+            count: row.get(2)?,
+        });
+    }
+    Ok(top_values)
+}
+
+/// Histograms of numeric columns, given with the finite minimum and maximum
+/// found by `column_stats`, counted in one scan of `relation`: equal-width
+/// bins between the minimum and maximum, or one bin for a column holding a
+/// single value
+fn histograms(
+    conn: &Connection,
+    relation: &str,
+    columns: &[(String, (f64, f64))],
+) -> Result<Vec<Vec<HistogramBin>>, AppError> {
+    if columns.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let bins = |(low, high): (f64, f64)| if high > low { HISTOGRAM_BINS } else { 1 };
+    let aggregates = columns
+        .iter()
+        .map(|(column, range)| {
+            let (low, high) = *range;
+            let bins = bins(*range);
+            let bin_sql = if bins > 1 {
+                let width = (high - low) / bins as f64;
+                format!("least(floor(({}::DOUBLE - {}) / {})::BIGINT, {})", column, low, width, bins - 1)
+            } else {
+                "0::BIGINT".to_string()
+            };
+            format!("histogram({}) FILTER (WHERE isfinite({}::DOUBLE))", bin_sql, column)
+        })
+        .collect::<Vec<_>>();
+
+    let sql = format!("SELECT {} FROM {}", aggregates.join(", "), relation);
+    let counts: Vec<Value> = conn.query_row(&sql, [], |row| (0..columns.len()).map(|i| row.get(i)).collect())?;
+
+    Ok(columns
+        .iter()
+        .zip(counts)
+        .map(|((_, range), counts)| {
+            let (low, high) = *range;
+            let bins = bins(*range);
+            let width = (high - low) / bins as f64;
+            let mut bin_counts = vec![0_i64; bins];
+            if let Value::Map(entries) = counts {
+                for (bin, count) in entries.iter() {
+                    let count = match count {
+                        Value::UBigInt(count) => *count as i64,
+                        Value::BigInt(count) => *count,
+                        _ => continue,
+                    };
+                    if let Value::BigInt(bin) = bin {
+                        if let Some(slot) = bin_counts.get_mut(*bin as usize) {
+                            *slot = count;
+                        }
+                    }
+                }
+            }
+            bin_counts
+                .into_iter()
+                .enumerate()
+                .map(|(i, count)| HistogramBin {
+                    lower: low + width * i as f64,
+                    upper: if i + 1 == bins { high } else { low + width * (i + 1) as f64 },
+                    count,
+                })
+                .collect()
+        })
+        .collect())
+}
+
+fn is_numeric(data_type: &str) -> bool {
+    NUMERIC_TYPES.contains(&data_type) || data_type.starts_with("DECIMAL")
+}
+
+fn is_nested(data_type: &str) -> bool {
+    data_type.ends_with(']')
+        || data_type.starts_with("STRUCT")
+        || data_type.starts_with("MAP")
+        || data_type.starts_with("UNION")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE measurements AS
+             SELECT i AS id,
+                    CASE WHEN i % 10 = 0 THEN NULL ELSE i % 4 END AS bucket,
+                    CASE WHEN i % 2 = 0 THEN 'even' ELSE 'odd' END AS parity,
+                    [i, i + 1] AS pair
+             FROM range(1, 101) t(i)",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_profile_table() {
+        let conn = sample_connection();
+        let profile = profile(&conn, "measurements", None, None, false).unwrap();
+        assert_eq!(profile.row_count, 100);
+        assert!(!profile.sampled);
+
+        let id = &profile.columns[0];
+        assert_eq!((id.name.as_str(), id.data_type.as_str()), ("id", "BIGINT"));
+        assert_eq!((id.null_count, id.distinct_count), (0, 100));
+        assert_eq!((id.min.as_deref(), id.max.as_deref()), (Some("1"), Some("100")));
+        assert_eq!(id.mean, Some(50.5));
+        assert_eq!(id.quantiles[1].quantile, 0.5);
+        assert_eq!(id.quantiles[1].value, 50.5);
+        assert_eq!(id.histogram.len(), HISTOGRAM_BINS);
+        assert_eq!(id.histogram.iter().map(|bin| bin.count).sum::<i64>(), 100);
+        assert_eq!((id.histogram[0].lower, id.histogram[HISTOGRAM_BINS - 1].upper), (1.0, 100.0));
+
+        let bucket = &profile.columns[1];
+        assert_eq!(bucket.null_count, 10);
+        assert_eq!(bucket.null_percent, 10.0);
+        assert_eq!(bucket.distinct_count, 4);
+        let bucket_top: Vec<_> = bucket.top_values.iter().map(|v| (v.value.as_deref(), v.count)).collect();
+        assert_eq!(bucket_top, [(Some("1"), 25), (Some("3"), 25), (Some("0"), 20), (Some("2"), 20), (None, 10)]);
+
+        let parity = &profile.columns[2];
+        assert_eq!(parity.mean, None);
+        assert!(parity.histogram.is_empty());
+        assert_eq!(parity.top_values.len(), 2);
+        assert_eq!(parity.top_values[0].count, 50);
+        assert_eq!(parity.top_values[0].value.as_deref(), Some("even"));
+
+        let pair = &profile.columns[3];
+        assert_eq!(pair.min, None);
+        assert_eq!(pair.top_values.len(), TOP_VALUES);
+    }
+
+    #[test]
+    fn test_profile_query_columns_and_sample() {
+        let conn = sample_connection();
+        let query = "(SELECT parity, count(*) AS n FROM measurements GROUP BY parity)";
+
+        let profile = profile(&conn, query, Some(&["n".to_string()]), None, true).unwrap();
+        assert_eq!(profile.row_count, 2);
+        assert_eq!(profile.columns.len(), 1);
+        let n = &profile.columns[0];
+        assert_eq!((n.distinct_count, n.histogram.len()), (1, 1));
+        assert_eq!(n.histogram[0].count, 2);
+
+        let sampled = super::profile(&conn, "measurements", None, Some(10), false).unwrap();
+        assert!(sampled.sampled);
+        assert_eq!(sampled.row_count, 10);
+
+        let error = super::profile(&conn, "measurements", Some(&["missing".to_string()]), None, false).unwrap_err();
+        assert_eq!(error.kind(), "invalidInput");
+
+        // Scratch tables are dropped again
+        let temp_tables: i64 = conn
+            .query_row("SELECT count(*) FROM duckdb_tables() WHERE temporary", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(temp_tables, 0);
+    }
+}
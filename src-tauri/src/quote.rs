@@ -0,0 +1,110 @@
+use std::path::Path;
+
+/// Quote an identifier (table, column, schema name) as `"name"`, doubling any
+/// embedded double quotes
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Quote a possibly catalog- and schema-qualified object name as
+/// `"database"."schema"."name"`, leaving out the parts not given
+pub fn qualified_name(database: Option<&str>, schema: Option<&str>, name: &str) -> String {
+    [database, schema, Some(name)]
+        .into_iter()
+        .flatten()
+        .map(quote_identifier)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Quote a string literal as `'text'`, doubling any embedded single quotes.
+/// Backslashes need no escaping in DuckDB string literals.
+pub fn quote_literal(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Quote a file path as a string literal for `read_csv_auto`, `COPY ... TO`
+/// and similar
+pub fn quote_path(path: impl AsRef<Path>) -> String {
+    quote_literal(&path.as_ref().to_string_lossy())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use duckdb::Connection;
+
+    const HOSTILE_NAMES: &[&str] = &[
+        "plain",
+        "Mixed Case",
+        "a\"b",
+        "\"; DROP TABLE victim; --",
+        "it's",
+        "dot.ted",
+        "back\\slash",
+        "ünïcödé",
+    ];
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("users"), "\"users\"");
+        assert_eq!(quote_identifier("a\"b"), "\"a\"\"b\"");
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE victim (x INT)").unwrap();
+        for name in HOSTILE_NAMES {
+            let table = quote_identifier(name);
+            let column = quote_identifier(&format!("{} col", name));
+            conn.execute_batch(&format!("CREATE TABLE {} ({} INT)", table, column))
+                .unwrap();
+            conn.execute_batch(&format!("INSERT INTO {} VALUES (1)", table))
+                .unwrap();
+            let sum: i64 = conn
+                .query_row(&format!("SELECT sum({}) FROM {}", column, table), [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(sum, 1, "{}", name);
+        }
+
+        let tables: i64 = conn
+            .query_row("SELECT count(*) FROM duckdb_tables()", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tables as usize, HOSTILE_NAMES.len() + 1);
+    }
+
+    #[test]
+    fn test_qualified_name() {
+        assert_eq!(qualified_name(None, None, "t"), "\"t\"");
+        assert_eq!(qualified_name(None, Some("s"), "t"), "\"s\".\"t\"");
+        assert_eq!(qualified_name(Some("d.b"), Some("s"), "a\"b"), "\"d.b\".\"s\".\"a\"\"b\"");
+    }
+
+    #[test]
+    fn test_quote_literal() {
+        assert_eq!(quote_literal("it's"), "'it''s'");
+
+        let conn = Connection::open_in_memory().unwrap();
+        for text in HOSTILE_NAMES.iter().chain(&["'); DROP TABLE victim; --", "''", "\n"]) {
+            let value: String = conn
+                .query_row(&format!("SELECT {}", quote_literal(text)), [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(value, *text);
+        }
+    }
+
+    #[test]
+    fn test_quote_path() {
+        let dir = std::env::temp_dir().join(format!("duckdb-viewer-quote-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("o'brien \"data\".csv");
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&format!("COPY (SELECT 42 AS answer) TO {}", quote_path(&path)))
+            .unwrap();
+        let answer: i64 = conn
+            .query_row(&format!("SELECT answer FROM read_csv_auto({})", quote_path(&path)), [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(answer, 42);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
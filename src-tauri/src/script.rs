@@ -0,0 +1,345 @@
+use duckdb::Connection;
+
+use crate::commands::execute_query_sync;
+use crate::error::AppError;
+use crate::models::{QueryResult, ScriptError, ScriptResult, StatementResult};
+use crate::values::ValueFormat;
+
+/// Leading keywords of statements whose result is always a result set, even
+/// when it happens to look like a `Count` column
+const QUERY_KEYWORDS: &[&str] = &[
+    "SELECT",
+    "WITH",
+    "FROM",
+    "VALUES",
+    "TABLE",
+    "SHOW",
+    "DESCRIBE",
+    "SUMMARIZE",
+    "EXPLAIN",
+    "PRAGMA",
+    "CALL",
+    "PIVOT",
+    "UNPIVOT",
+    "(",
+];
+
+/// A statement of a script and the byte offset it starts at
+#[derive(Debug, PartialEq)]
+struct ScriptStatement<'a> {
+    offset: usize,
+    sql: &'a str,
+}
+
+/// Run every statement of `script` in order on `conn`, stopping at the first
+/// failure. Statements are not wrapped in a transaction, so those before a
+/// failing one stay applied. DuckDB parses each statement as it comes to it,
+/// so a syntax error is reported like any other failure, at its statement.
+pub fn execute_script(conn: &Connection, script: &str, value_format: &ValueFormat) -> ScriptResult {
+    let start = std::time::Instant::now();
+
+    let mut outcome = ScriptResult {
+        statements: Vec::new(),
+        error: None,
+        execution_time_ms: 0,
+    };
+
+    for (index, statement) in split_statements(script).iter().enumerate() {
+        match execute_statement(conn, index, statement.sql, value_format) {
+            Ok(result) => outcome.statements.push(result),
+            Err(error) => {
+                outcome.error = Some(statement_error(script, statement, index, &error));
+                break;
+            }
+        }
+    }
+
+    outcome.execution_time_ms = start.elapsed().as_millis();
+    outcome
+}
+
+fn execute_statement(
+    conn: &Connection,
+    index: usize,
+    sql: &str,
+    value_format: &ValueFormat,
+) -> Result<StatementResult, AppError> {
+    let result = execute_query_sync(conn, sql, value_format)?;
+    let execution_time_ms = result.execution_time_ms;
+
+    let (result, rows_affected) = if is_query(sql) {
+        (Some(result), None)
+    } else if is_count_result(&result) {
+        let count = result.rows.first().and_then(|row| row[0].as_i64());
+        (None, count)
+    } else if result.columns == ["Success"] && result.row_count == 0 {
+        (None, None)
+    } else {
+        (Some(result), None)
+    };
+
+    Ok(StatementResult {
+        index,
+        sql: sql.to_string(),
+        result,
+        rows_affected,
+        execution_time_ms,
+    })
+}
+
+fn is_query(sql: &str) -> bool {
+    let keyword = if sql.starts_with('(') {
+        "("
+    } else {
+        sql.split(|c: char| !c.is_ascii_alphabetic())
+            .next()
+            .unwrap_or("")
+    };
+    QUERY_KEYWORDS
+        .iter()
+        .any(|k| k.eq_ignore_ascii_case(keyword))
+}
+
+/// DML, CREATE TABLE AS and COPY report the rows they touched as a single
+/// BIGINT `Count` column
+fn is_count_result(result: &QueryResult) -> bool {
+    result.columns == ["Count"] && result.column_types == ["BIGINT"]
+}
+
+/// Error for a statement that failed to run, at the position DuckDB reports
+/// within the statement or, without one, at the statement's start
+fn statement_error(
+    script: &str,
+    statement: &ScriptStatement,
+    index: usize,
+    error: &AppError,
+) -> ScriptError {
+    let before = &script[..statement.offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+
+    let (line, column) = match error.location() {
+        Some(location) if location.line > 1 => {
+            (line + location.line - 1, location.column.unwrap_or(1))
+        }
+        Some(location) => (line, column + location.column.map_or(0, |c| c - 1)),
+        None => (line, column),
+    };
+
+    ScriptError {
+        statement_index: index,
+        line,
+        column,
+        message: error.to_string(),
+    }
+}
+
+/// Split a script into its statements in one pass over the text, at each
+/// semicolon outside string literals, quoted identifiers, dollar-quoted
+/// strings and comments. Empty statements are dropped. Each statement is then
+/// parsed by DuckDB when it runs, so text this does not recognise still fails
+/// there with DuckDB's own error.
+fn split_statements(script: &str) -> Vec<ScriptStatement<'_>> {
+    let bytes = script.as_bytes();
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        i = match bytes[i] {
+            b';' => {
+                let statement = trimmed_statement(script, start, i);
+                if !statement.sql.is_empty() {
+                    statements.push(statement);
+                }
+                start = i + 1;
+                i + 1
+            }
+            b'\'' => {
+                // In E'...' strings a backslash escapes the next character
+                let escapes = i > 0
+                    && bytes[i - 1].eq_ignore_ascii_case(&b'e')
+                    && (i < 2 || !is_identifier_byte(bytes[i - 2]));
+                skip_quoted(bytes, i, b'\'', escapes)
+            }
+            b'"' => skip_quoted(bytes, i, b'"', false),
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                script[i..].find('\n').map_or(bytes.len(), |newline| i + newline + 1)
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => skip_block_comment(bytes, i),
+            b'$' if i == 0 || !is_identifier_byte(bytes[i - 1]) => skip_dollar_quoted(script, i),
+            _ => i + 1,
+        };
+    }
+
+    let statement = trimmed_statement(script, start, script.len());
+    if !statement.sql.is_empty() {
+        statements.push(statement);
+    }
+    statements
+}
+
+fn is_identifier_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+}
+
+/// End of the literal opening with `quote` at `start`; a doubled quote stands
+/// for itself. An unterminated literal runs to the end of the script.
+fn skip_quoted(bytes: &[u8], start: usize, quote: u8, escapes: bool) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if escapes => i += 2,
+            byte if byte == quote && bytes.get(i + 1) == Some(&quote) => i += 2,
+            byte if byte == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// End of the block comment opening at `start`; block comments nest
+fn skip_block_comment(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"/*") {
+            depth += 1;
+            i += 2;
+        } else if bytes[i..].starts_with(b"*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    bytes.len()
+}
+
+/// End of the `$tag$...$tag$` string opening at `start`, or just past the `$`
+/// if none opens there, as in a `$1` parameter
+fn skip_dollar_quoted(script: &str, start: usize) -> usize {
+    let rest = &script[start + 1..];
+    let tag_len = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+    let tag = &rest[..tag_len];
+    if !rest[tag_len..].starts_with('$') || tag.starts_with(|c: char| c.is_ascii_digit()) {
+        return start + 1;
+    }
+
+    let delimiter = &script[start..start + tag_len + 2];
+    let body = start + delimiter.len();
+    script[body..]
+        .find(delimiter)
+        .map_or(script.len(), |end| body + end + delimiter.len())
+}
+
+/// The statement in `script[start..end]` without the whitespace, empty
+/// statements and comments around it, so its offset is that of its first token
+fn trimmed_statement(script: &str, start: usize, end: usize) -> ScriptStatement<'_> {
+    let mut offset = start;
+    loop {
+        let rest = script[offset..end].trim_start_matches(|c: char| c == ';' || c.is_whitespace());
+        offset = end - rest.len();
+        if rest.starts_with("--") {
+            offset += rest.find('\n').map_or(rest.len(), |newline| newline + 1);
+        } else if rest.starts_with("/*") {
+            offset += rest.find("*/").map_or(rest.len(), |close| close + 2);
+        } else {
+            break;
+        }
+    }
+    ScriptStatement {
+        offset,
+        sql: script[offset..end].trim_end(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sqls(script: &str) -> Vec<&str> {
+        split_statements(script).into_iter().map(|s| s.sql).collect()
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            sqls("SELECT 'a;b'; SELECT \"x;y\" FROM t;\n-- done; really\nSELECT $$;$$ /* ; */"),
+            vec![
+                "SELECT 'a;b'",
+                "SELECT \"x;y\" FROM t",
+                "SELECT $$;$$ /* ; */"
+            ]
+        );
+        assert_eq!(
+            sqls("SELECT 'it''s;'; SELECT E'\\';'"),
+            vec!["SELECT 'it''s;'", "SELECT E'\\';'"]
+        );
+        // A backslash ends no literal unless it is an E'...' string
+        assert_eq!(
+            sqls("SELECT * FROM t WHERE name LIKE'%\\';\nSELECT 2"),
+            vec!["SELECT * FROM t WHERE name LIKE'%\\'", "SELECT 2"]
+        );
+        assert_eq!(
+            sqls("SELECT $tag$a;$b$tag$;; SELECT $1"),
+            vec!["SELECT $tag$a;$b$tag$", "SELECT $1"]
+        );
+        assert_eq!(
+            sqls("SELECT 1 /* outer /* inner; */ still; */; SELECT \"a\"\"b;\""),
+            vec!["SELECT 1 /* outer /* inner; */ still; */", "SELECT \"a\"\"b;\""]
+        );
+        assert_eq!(sqls(" ; -- only a comment\n;"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_execute_script_outcomes() {
+        let conn = Connection::open_in_memory().unwrap();
+        let script = "CREATE TABLE t (s VARCHAR);\nINSERT INTO t VALUES ('a;b'), ('c');\n-- rows\nSELECT * FROM t ORDER BY s;\nSET threads = 2";
+        let outcome = execute_script(&conn, script, &ValueFormat::default());
+
+        assert!(outcome.error.is_none());
+        assert_eq!(outcome.statements.len(), 4);
+
+        assert!(outcome.statements[0].result.is_none());
+        assert_eq!(outcome.statements[1].rows_affected, Some(2));
+        let rows = &outcome.statements[2].result.as_ref().unwrap().rows;
+        assert_eq!(rows[0][0], serde_json::Value::from("a;b"));
+        assert!(outcome.statements[3].result.is_none());
+        assert_eq!(outcome.statements[3].rows_affected, None);
+    }
+
+    #[test]
+    fn test_execute_script_stops_at_first_error() {
+        let conn = Connection::open_in_memory().unwrap();
+        let script =
+            "CREATE TABLE t (i INTEGER);\n  SELECT i, missing FROM t;\nDROP TABLE t;";
+        let outcome = execute_script(&conn, script, &ValueFormat::default());
+
+        assert_eq!(outcome.statements.len(), 1);
+        let error = outcome.error.unwrap();
+        assert_eq!(error.statement_index, 1);
+        // At the missing column, not the start of the statement
+        assert_eq!((error.line, error.column), (2, 13));
+        assert!(error.message.contains("missing"));
+
+        // The table created before the failure is kept; the DROP never ran
+        assert!(conn.execute("SELECT * FROM t", []).is_ok());
+    }
+
+    #[test]
+    fn test_syntax_error_stops_at_its_statement() {
+        let conn = Connection::open_in_memory().unwrap();
+        let script = "CREATE TABLE t (i INTEGER);\nSELECT 1;\n  FORM t;";
+        let outcome = execute_script(&conn, script, &ValueFormat::default());
+
+        assert_eq!(outcome.statements.len(), 2);
+        let error = outcome.error.unwrap();
+        assert_eq!(error.statement_index, 2);
+        assert_eq!((error.line, error.column), (3, 3));
+        assert!(error.message.contains("syntax error"), "{}", error.message);
+    }
+}
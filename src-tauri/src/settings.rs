@@ -0,0 +1,336 @@
+use std::sync::RwLock;
+use std::time::Duration;
+
+use chrono::FixedOffset;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_store::StoreExt;
+
+use crate::error::AppError;
+
+/// Store file of the backend settings, kept apart from the frontend's
+/// `settings.json` (dashboards, recent databases)
+const SETTINGS_STORE: &str = "backend-settings.json";
+
+/// Key of the settings object inside the store
+const SETTINGS_KEY: &str = "settings";
+
+/// Kinds of database work that each get their own timeout
+#[derive(Debug, Clone, Copy)]
+pub enum OperationKind {
+    Query,
+    Import,
+    Export,
+    Metadata,
+}
+
+/// Timeouts in milliseconds. Imports and exports may use `None` to run
+/// without a timeout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeoutSettings {
+    pub query_ms: u64,
+    pub import_ms: Option<u64>,
+    pub export_ms: Option<u64>,
+    pub metadata_ms: u64,
+}
+
+impl Default for TimeoutSettings {
+    fn default() -> Self {
+        Self {
+            query_ms: 30_000,
+            import_ms: None,
+            export_ms: None,
+            metadata_ms: 30_000,
+        }
+    }
+}
+
+impl TimeoutSettings {
+    pub fn for_operation(&self, kind: OperationKind) -> Option<Duration> {
+        let ms = match kind {
+            OperationKind::Query => Some(self.query_ms),
+            OperationKind::Import => self.import_ms,
+            OperationKind::Export => self.export_ms,
+            OperationKind::Metadata => Some(self.metadata_ms),
+        };
+        ms.map(Duration::from_millis)
+    }
+
+    fn validate(&self) -> Result<(), AppError> {
+        let all = [
+            Some(self.query_ms),
+            self.import_ms,
+            self.export_ms,
+            Some(self.metadata_ms),
+        ];
+        all.into_iter().flatten().try_for_each(validate_timeout)
+    }
+}
+
+/// Reject a timeout of zero milliseconds, which would stop every operation
+/// before it starts
+fn validate_timeout(ms: u64) -> Result<(), AppError> {
+    if ms == 0 {
+        return Err(AppError::invalid_input("Timeouts must be greater than zero"));
+    }
+    Ok(())
+}
+
+/// Time zone TIMESTAMPTZ values are displayed in. Stored as `"local"`,
+/// `"UTC"` or a fixed offset such as `"+09:00"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum DisplayTimezone {
+    #[default]
+    Local,
+    Utc,
+    Fixed(FixedOffset),
+}
+
+impl TryFrom<String> for DisplayTimezone {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "local" => Ok(Self::Local),
+            "UTC" | "Z" => Ok(Self::Utc),
+            offset => offset
+                .parse()
+                .map(Self::Fixed)
+                .map_err(|_| format!("Invalid display timezone: {}", value)),
+        }
+    }
+}
+
+impl From<DisplayTimezone> for String {
+    fn from(timezone: DisplayTimezone) -> Self {
+        match timezone {
+            DisplayTimezone::Local => "local".to_string(),
+            DisplayTimezone::Utc => "UTC".to_string(),
+            DisplayTimezone::Fixed(offset) => offset.to_string(),
+        }
+    }
+}
+
+/// How query results are rendered for display and export
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub timezone: DisplayTimezone,
+}
+
+/// Backend settings persisted in their own `tauri-plugin-store` file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub timeouts: TimeoutSettings,
+    pub display: DisplaySettings,
+}
+
+/// Holds the current settings using RwLock for thread-safe access
+pub struct SettingsManager {
+    settings: RwLock<Settings>,
+}
+
+impl SettingsManager {
+    /// Load the persisted settings, falling back to defaults if they are
+    /// missing or unreadable
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        Self::from_stored(app.store(SETTINGS_STORE).ok().and_then(|store| store.get(SETTINGS_KEY)))
+    }
+
+    /// Manager for the settings stored as `value`, with defaults for a
+    /// missing value and for missing or unreadable fields. A field that cannot
+    /// be read is logged and keeps its default; the others still load.
+    fn from_stored(value: Option<JsonValue>) -> Self {
+        let mut merged = serde_json::to_value(Settings::default()).unwrap_or_default();
+        if let Some(value) = value {
+            merge_readable(&mut merged, "", value);
+        }
+        let settings = serde_json::from_value(merged).unwrap_or_default();
+
+        Self {
+            settings: RwLock::new(settings),
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings
+            .read()
+            .map(|guard| guard.clone())
+            .unwrap_or_default()
+    }
+
+    /// Timeout configured for `kind`, or `None` for no timeout
+    pub fn timeout(&self, kind: OperationKind) -> Option<Duration> {
+        self.get().timeouts.for_operation(kind)
+    }
+
+    /// Timeout of a query run with `timeout_ms`, the caller's override of the
+    /// configured query timeout
+    pub fn query_timeout(&self, timeout_ms: Option<u64>) -> Result<Option<Duration>, AppError> {
+        match timeout_ms {
+            Some(ms) => validate_timeout(ms).map(|_| Some(Duration::from_millis(ms))),
+            None => Ok(self.timeout(OperationKind::Query)),
+        }
+    }
+
+    pub fn display(&self) -> DisplaySettings {
+        self.get().display
+    }
+
+    /// Validate, persist and apply new settings
+    pub fn update<R: Runtime>(&self, app: &AppHandle<R>, settings: Settings) -> Result<(), AppError> {
+        self.apply(settings, |value| {
+            let store = app
+                .store(SETTINGS_STORE)
+                .map_err(|e| AppError::Io {
+                    message: format!("Failed to open settings store: {}", e),
+                })?;
+            store.set(SETTINGS_KEY, value);
+            store
+                .save()
+                .map_err(|e| AppError::Io {
+                    message: format!("Failed to save settings: {}", e),
+                })
+        })
+    }
+
+    /// Validate `settings`, hand their stored form to `persist` and apply
+    /// them once that succeeds
+    fn apply(
+        &self,
+        settings: Settings,
+        persist: impl FnOnce(JsonValue) -> Result<(), AppError>,
+    ) -> Result<(), AppError> {
+        settings.timeouts.validate()?;
+        persist(serde_json::to_value(&settings)?)?;
+
+        if let Ok(mut guard) = self.settings.write() {
+            *guard = settings;
+        }
+        Ok(())
+    }
+}
+
+/// Copy the fields of `stored` into the object at `pointer` in `merged`, the
+/// stored form of a whole [`Settings`], one field at a time, skipping any
+/// that would leave it unreadable
+fn merge_readable(merged: &mut JsonValue, pointer: &str, stored: JsonValue) {
+    let JsonValue::Object(fields) = stored else {
+        log::warn!("Ignoring stored settings at '{}': expected an object", pointer);
+        return;
+    };
+
+    for (name, value) in fields {
+        let field_pointer = format!("{}/{}", pointer, name);
+        if value.is_object() && merged.pointer(&field_pointer).is_some_and(JsonValue::is_object) {
+            merge_readable(merged, &field_pointer, value);
+            continue;
+        }
+
+        let Some(JsonValue::Object(object)) = merged.pointer_mut(pointer) else {
+            return;
+        };
+        let previous = object.insert(name.clone(), value);
+        if let Err(e) = serde_json::from_value::<Settings>(merged.clone()) {
+            log::warn!("Ignoring stored setting {}: {}", field_pointer, e);
+            if let Some(JsonValue::Object(object)) = merged.pointer_mut(pointer) {
+                match previous {
+                    Some(previous) => object.insert(name, previous),
+                    None => object.remove(&name),
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_load_fills_in_defaults() {
+        let manager = SettingsManager::from_stored(None);
+        assert_eq!(manager.timeout(OperationKind::Query), Some(Duration::from_secs(30)));
+        assert_eq!(manager.timeout(OperationKind::Import), None);
+        assert_eq!(manager.display().timezone, DisplayTimezone::Local);
+
+        let manager = SettingsManager::from_stored(Some(json!({
+            "timeouts": {"query_ms": 5000, "export_ms": 60000},
+            "display": {"timezone": "+09:00"}
+        })));
+        assert_eq!(manager.timeout(OperationKind::Query), Some(Duration::from_secs(5)));
+        assert_eq!(manager.timeout(OperationKind::Export), Some(Duration::from_secs(60)));
+        assert_eq!(manager.timeout(OperationKind::Metadata), Some(Duration::from_secs(30)));
+        assert_eq!(String::from(manager.display().timezone), "+09:00");
+
+        // An unreadable field keeps its default without resetting the others
+        let manager = SettingsManager::from_stored(Some(json!({
+            "timeouts": {"query_ms": 5000, "metadata_ms": "soon"},
+            "display": {"timezone": "Mars/Olympus"}
+        })));
+        assert_eq!(manager.display().timezone, DisplayTimezone::Local);
+        assert_eq!(manager.timeout(OperationKind::Query), Some(Duration::from_secs(5)));
+        assert_eq!(manager.timeout(OperationKind::Metadata), Some(Duration::from_secs(30)));
+
+        let manager = SettingsManager::from_stored(Some(json!({"timeouts": 5, "display": {"timezone": "UTC"}})));
+        assert_eq!(manager.timeout(OperationKind::Query), Some(Duration::from_secs(30)));
+        assert_eq!(manager.display().timezone, DisplayTimezone::Utc);
+    }
+
+    #[test]
+    fn test_query_timeout_override() {
+        let manager = SettingsManager::from_stored(None);
+        assert_eq!(manager.query_timeout(None).unwrap(), Some(Duration::from_secs(30)));
+        assert_eq!(manager.query_timeout(Some(250)).unwrap(), Some(Duration::from_millis(250)));
+        assert_eq!(manager.query_timeout(Some(0)).unwrap_err().kind(), "invalidInput");
+    }
+
+    #[test]
+    fn test_update_validates_persists_and_applies() {
+        let manager = SettingsManager::from_stored(None);
+        let mut stored = None;
+
+        let mut settings = Settings::default();
+        settings.timeouts.import_ms = Some(0);
+        let error = manager.apply(settings, |value| {
+            stored = Some(value);
+            Ok(())
+        });
+        assert_eq!(error.unwrap_err().kind(), "invalidInput");
+        assert!(stored.is_none());
+
+        let mut settings = Settings::default();
+        settings.timeouts.query_ms = 1500;
+        settings.display.timezone = DisplayTimezone::Utc;
+        let error = manager.apply(settings.clone(), |_| Err(AppError::internal("disk full")));
+        assert!(error.is_err());
+        assert_eq!(manager.timeout(OperationKind::Query), Some(Duration::from_secs(30)));
+
+        manager
+            .apply(settings, |value| {
+                stored = Some(value);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(manager.timeout(OperationKind::Query), Some(Duration::from_millis(1500)));
+
+        let reloaded = SettingsManager::from_stored(stored);
+        assert_eq!(reloaded.timeout(OperationKind::Query), Some(Duration::from_millis(1500)));
+        assert_eq!(reloaded.display().timezone, DisplayTimezone::Utc);
+    }
+
+    #[test]
+    fn test_display_timezone_round_trip() {
+        for text in ["local", "UTC", "+09:00", "-05:30"] {
+            let timezone = DisplayTimezone::try_from(text.to_string()).unwrap();
+            assert_eq!(String::from(timezone), text);
+        }
+        assert_eq!(DisplayTimezone::try_from("Z".to_string()), Ok(DisplayTimezone::Utc));
+        assert!(DisplayTimezone::try_from("+25:00".to_string()).is_err());
+    }
+}
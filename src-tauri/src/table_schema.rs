@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use duckdb::types::Value;
+use duckdb::{params, Connection};
+
+use crate::databases::current_catalog;
+use crate::error::AppError;
+use crate::models::{CheckConstraint, ColumnInfo, ForeignKey, IndexInfo, TableSchema, UniqueConstraint};
+use crate::quote::qualified_name;
+
+/// Columns, constraints, comments and indexes of the table or view
+/// `table_name`, looked up in `database` and `schema` when given and in the
+/// session's defaults otherwise.
+///
+/// DuckDB reports a generated column's expression as its default in
+/// `duckdb_columns()`, so it is only taken as one where
+/// `information_schema.columns.is_generated` says the column is generated.
+/// DuckDB 1.4 leaves that column NULL, and until it fills it in no
+/// `generated_expression` is reported.
+pub fn get_table_schema(
+    conn: &Connection,
+    table_name: &str,
+    database: Option<&str>,
+    schema: Option<&str>,
+) -> Result<TableSchema, AppError> {
+    let (default_database, default_schema) = current_catalog(conn)?;
+    let database = database.map_or(default_database, str::to_string);
+    let schema = schema.map_or(default_schema, str::to_string);
+    let table = params![database, schema, table_name];
+
+    let mut column_details: HashMap<String, (Option<String>, Option<String>)> = conn
+        .prepare(
+            "SELECT c.column_name, c.comment, c.column_default, i.is_generated
+             FROM duckdb_columns() c
+             LEFT JOIN information_schema.columns i
+               ON i.table_catalog = c.database_name AND i.table_schema = c.schema_name
+              AND i.table_name = c.table_name AND i.column_name = c.column_name
+             WHERE c.database_name = ? AND c.schema_name = ? AND c.table_name = ?",
+        )?
+        .query_map(table, |row| {
+            let generated = row
+                .get::<_, Option<String>>(3)?
+                .is_some_and(|is_generated| is_generated.eq_ignore_ascii_case("ALWAYS"));
+            Ok((row.get(0)?, (row.get(1)?, row.get::<_, Option<String>>(2)?.filter(|_| generated))))
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "DESCRIBE {}",
+        qualified_name(Some(&database), Some(&schema), table_name)
+    ))?;
+    let columns = stmt
+        .query_map([], |row| {
+            let name: String = row.get(0)?;
+            let default_value: Option<String> = row.get(4)?;
+            let (comment, generated_expression) = column_details.remove(&name).unwrap_or_default();
+
+            Ok(ColumnInfo {
+                data_type: row.get(1)?,
+                nullable: row.get::<_, String>(2)? == "YES",
+                is_primary_key: row.get::<_, Option<String>>(3)?.as_deref() == Some("PRI"),
+                generated_expression,
+                default_value,
+                comment,
+                name,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let comment: Option<String> = conn
+        .query_row(
+            "SELECT comment FROM duckdb_tables()
+             WHERE database_name = ? AND schema_name = ? AND table_name = ?
+             UNION ALL
+             SELECT comment FROM duckdb_views()
+             WHERE database_name = ? AND schema_name = ? AND view_name = ?",
+            params![database, schema, table_name, database, schema, table_name],
+            |row| row.get(0),
+        )?;
+
+    let mut unique_constraints = Vec::new();
+    let mut check_constraints = Vec::new();
+    let mut foreign_keys = Vec::new();
+    let mut stmt = conn.prepare(
+        "SELECT constraint_type, constraint_name, constraint_column_names,
+                expression, referenced_table, referenced_column_names
+         FROM duckdb_constraints()
+         WHERE database_name = ? AND schema_name = ? AND table_name = ?
+           AND constraint_type IN ('UNIQUE', 'CHECK', 'FOREIGN KEY')
+         ORDER BY constraint_index",
+    )?;
+    let mut rows = stmt.query(table)?;
+    while let Some(row) = rows.next()? {
+        let name: String = row.get(1)?;
+        let columns = string_list(row.get(2)?);
+
+        match row.get::<_, String>(0)?.as_str() {
+            "UNIQUE" => unique_constraints.push(UniqueConstraint { name, columns }),
+            "CHECK" => check_constraints.push(CheckConstraint {
+                name,
+                expression: row.get(3)?,
+            }),
+            _ => foreign_keys.push(ForeignKey {
+                name,
+                columns,
+                referenced_table: row.get(4)?,
+                referenced_columns: string_list(row.get(5)?),
+            }),
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT index_name, is_unique, expressions, sql FROM duckdb_indexes()
+         WHERE database_name = ? AND schema_name = ? AND table_name = ?
+         ORDER BY index_name",
+    )?;
+    let indexes = stmt
+        .query_map(table, |row| {
+            Ok(IndexInfo {
+                name: row.get(0)?,
+                is_unique: row.get(1)?,
+                expressions: row.get(2)?,
+                sql: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(TableSchema {
+        table_name: table_name.to_string(),
+        database,
+        schema,
+        comment,
+        columns,
+        unique_constraints,
+        check_constraints,
+        foreign_keys,
+        indexes,
+    })
+}
+
+/// The strings of a `VARCHAR[]` catalog column
+pub fn string_list(value: Value) -> Vec<String> {
+    match value {
+        Value::List(items) => items
+            .into_iter()
+            .filter_map(|item| match item {
+                Value::Text(text) => Some(text),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_schema_metadata() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE SCHEMA shop;
+             CREATE TABLE shop.customers (id INTEGER PRIMARY KEY, email VARCHAR UNIQUE);
+             CREATE TABLE shop.\"order's\" (
+                 id INTEGER PRIMARY KEY,
+                 customer_id INTEGER REFERENCES shop.customers (id),
+                 quantity INTEGER NOT NULL CHECK (quantity > 0),
+                 unit_price DECIMAL(10, 2) DEFAULT 0,
+                 total DECIMAL(12, 2) GENERATED ALWAYS AS (quantity * unit_price),
+                 \"Line note\" VARCHAR GENERATED ALWAYS AS ('qty ' || quantity),
+                 UNIQUE (customer_id, quantity)
+             );
+             COMMENT ON TABLE shop.\"order's\" IS 'Orders placed online';
+             COMMENT ON COLUMN shop.\"order's\".quantity IS 'Units ordered';
+             CREATE INDEX order_lookup ON shop.\"order's\" (customer_id, (quantity + 1));",
+        )
+        .unwrap();
+
+        let schema = get_table_schema(&conn, "order's", None, Some("shop")).unwrap();
+        assert_eq!((schema.database.as_str(), schema.schema.as_str()), ("memory", "shop"));
+        assert_eq!(schema.comment.as_deref(), Some("Orders placed online"));
+
+        let column = |name: &str| schema.columns.iter().find(|c| c.name == name).unwrap();
+        assert!(column("id").is_primary_key);
+        assert_eq!(column("quantity").comment.as_deref(), Some("Units ordered"));
+        assert_eq!(column("unit_price").default_value.as_deref(), Some("0"));
+        assert_eq!(column("unit_price").generated_expression, None);
+        // DuckDB does not say which columns are generated, so their
+        // expressions are not reported rather than mistaken for defaults
+        assert_eq!(column("total").generated_expression, None);
+        assert_eq!(column("total").default_value, None);
+        assert_eq!(column("Line note").generated_expression, None);
+
+        assert_eq!(schema.unique_constraints.len(), 1);
+        assert_eq!(schema.unique_constraints[0].columns, vec!["customer_id", "quantity"]);
+        assert_eq!(schema.check_constraints.len(), 1);
+        assert_eq!(schema.check_constraints[0].expression.as_deref(), Some("(quantity > 0)"));
+        assert_eq!(schema.foreign_keys.len(), 1);
+        let foreign_key = &schema.foreign_keys[0];
+        assert_eq!(foreign_key.columns, vec!["customer_id"]);
+        assert_eq!(foreign_key.referenced_table.as_deref(), Some("customers"));
+        assert_eq!(foreign_key.referenced_columns, vec!["id"]);
+
+        assert_eq!(schema.indexes.len(), 1);
+        assert_eq!(schema.indexes[0].name, "order_lookup");
+        assert!(!schema.indexes[0].is_unique);
+        assert_eq!(schema.indexes[0].expressions.as_deref(), Some("[customer_id, '((quantity + 1))']"));
+
+        let customers = get_table_schema(&conn, "customers", Some("memory"), Some("shop")).unwrap();
+        assert_eq!(customers.comment, None);
+        assert_eq!(customers.unique_constraints[0].columns, vec!["email"]);
+    }
+}
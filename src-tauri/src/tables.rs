@@ -0,0 +1,285 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use duckdb::Connection;
+
+use crate::db::{InterruptGroup, TEMP_CATALOG};
+use crate::error::AppError;
+use crate::file_views::is_file_view;
+use crate::models::{TableInfo, TableType};
+use crate::quote::{qualified_name, quote_literal};
+
+/// Upper bound on the connections counting tables at the same time
+const MAX_STATS_WORKERS: usize = 8;
+
+/// Tables and views of every database in the session, those of the default
+/// database first.
+///
+/// Row counts come from DuckDB's catalog estimates, which are cheap but
+/// ignore deleted rows, unless `exact_counts` asks for a `COUNT(*)` of every
+/// table. Storage sizes are only looked up with `storage_sizes`, as that
+/// walks the storage metadata of every table. Both are gathered in parallel
+/// on clones of `conn`, which join `interrupts` so they stop along with the
+/// query, except for temporary tables, which only `conn` can see.
+pub fn list_tables(
+    conn: &Connection,
+    exact_counts: bool,
+    storage_sizes: bool,
+    interrupts: &InterruptGroup,
+) -> Result<Vec<TableInfo>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT database_name, schema_name, table_name, is_view, comment, estimated_size
+         FROM (
+             SELECT database_name, schema_name, table_name, false AS is_view, comment, estimated_size
+             FROM duckdb_tables()
+             WHERE NOT internal
+             UNION ALL
+             SELECT database_name, schema_name, view_name, true, comment, NULL
+             FROM duckdb_views()
+             WHERE NOT internal
+         )
+         ORDER BY database_name = current_database() DESC, database_name, schema_name, table_name",
+    )?;
+    let mut tables = stmt
+        .query_map([], |row| {
+            let comment: Option<String> = row.get(4)?;
+            Ok(TableInfo {
+                name: row.get(2)?,
+                database: row.get(0)?,
+                schema: row.get(1)?,
+                table_type: match row.get(3)? {
+                    true if is_file_view(comment.as_deref()) => TableType::FileView,
+                    true => TableType::View,
+                    false => TableType::Table,
+                },
+                row_count: row.get(5)?,
+                row_count_exact: false,
+                row_count_error: None,
+                size_bytes: None,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if !exact_counts && !storage_sizes {
+        return Ok(tables);
+    }
+    let (temporary, shared): (Vec<usize>, Vec<usize>) = (0..tables.len())
+        .filter(|&i| tables[i].table_type == TableType::Table)
+        .partition(|&i| tables[i].database == TEMP_CATALOG);
+
+    for i in temporary {
+        if interrupts.is_interrupted() {
+            return Err(AppError::Cancelled);
+        }
+        let stats = table_stats(conn, &tables[i], exact_counts, storage_sizes);
+        stats.apply(&mut tables[i]);
+    }
+
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_STATS_WORKERS)
+        .min(shared.len());
+    let connections = (0..workers)
+        .map(|_| conn.try_clone())
+        .collect::<Result<Vec<_>, _>>()?;
+    let next = AtomicUsize::new(0);
+
+    let results: Vec<(usize, TableStats)> = thread::scope(|scope| {
+        let workers: Vec<_> = connections
+            .into_iter()
+            .map(|worker_conn| {
+                let (tables, shared, next) = (&tables, &shared, &next);
+                scope.spawn(move || {
+                    let mut results = Vec::new();
+                    if !interrupts.add(&worker_conn) {
+                        return results;
+                    }
+                    while let Some(&i) = shared.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        if interrupts.is_interrupted() {
+                            break;
+                        }
+                        results.push((i, table_stats(&worker_conn, &tables[i], exact_counts, storage_sizes)));
+                    }
+                    results
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .map(|worker| worker.join().map_err(|panic| worker_panic(panic.as_ref())))
+            .collect::<Result<Vec<_>, _>>()
+            .map(|results| results.into_iter().flatten().collect())
+    })?;
+
+    if interrupts.is_interrupted() {
+        return Err(AppError::Cancelled);
+    }
+    for (i, stats) in results {
+        stats.apply(&mut tables[i]);
+    }
+
+    Ok(tables)
+}
+
+fn worker_panic(panic: &(dyn std::any::Any + Send)) -> AppError {
+    let message = panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown cause");
+    AppError::internal(format!("A table statistics worker panicked: {}", message))
+}
+
+/// What a worker found out about one table
+struct TableStats {
+    row_count: Option<Result<i64, AppError>>,
+    size_bytes: Option<i64>,
+}
+
+impl TableStats {
+    fn apply(self, table: &mut TableInfo) {
+        match self.row_count {
+            Some(Ok(count)) => {
+                table.row_count = Some(count);
+                table.row_count_exact = true;
+            }
+            Some(Err(e)) => {
+                table.row_count = None;
+                table.row_count_error = Some(e.to_string());
+            }
+            None => {}
+        }
+        table.size_bytes = self.size_bytes;
+    }
+}
+
+fn table_stats(conn: &Connection, table: &TableInfo, exact_count: bool, storage_size: bool) -> TableStats {
+    let name = qualified_name(Some(&table.database), Some(&table.schema), &table.name);
+
+    let row_count = exact_count.then(|| {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", name), [], |row| row.get(0))
+            .map_err(AppError::from)
+    });
+
+    TableStats {
+        row_count,
+        size_bytes: storage_size
+            .then(|| table_storage_size(conn, &table.database, &name).ok().flatten())
+            .flatten(),
+    }
+}
+
+/// Bytes of the database-file blocks holding the table `name`, or None while
+/// some of its data is only in memory (in-memory databases, changes not yet
+/// checkpointed). Blocks are counted whole, so small tables sharing a block
+/// each report all of it. `pragma_storage_info` cannot resolve names with
+/// embedded double quotes, so those tables fail here.
+fn table_storage_size(conn: &Connection, database: &str, name: &str) -> Result<Option<i64>, AppError> {
+    let (blocks, in_memory, block_size): (i64, bool, Option<i64>) = conn.query_row(
+        &format!(
+            "SELECT count(DISTINCT block_id) FILTER (WHERE persistent),
+                    coalesce(bool_or(NOT persistent), false),
+                    (SELECT block_size FROM pragma_database_size() WHERE database_name = {})
+             FROM pragma_storage_info({})",
+            quote_literal(database),
+            quote_literal(name)
+        ),
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+
+    Ok(match (in_memory, block_size) {
+        (false, Some(block_size)) => Some(blocks * block_size),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_views::register_file_view;
+    use crate::models::FileViewFormat;
+    use crate::quote::quote_path;
+
+    #[test]
+    fn test_list_tables_distinguishes_views() {
+        let path = std::env::temp_dir().join(format!("duckdb-viewer-list-{}.csv", std::process::id()));
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&format!(
+            "CREATE TABLE users AS SELECT i AS id, 'Tokyo' AS city FROM range(10) t(i);
+             COPY users TO {};
+             CREATE VIEW tokyo_users AS SELECT * FROM users WHERE city = 'Tokyo';",
+            quote_path(&path)
+        ))
+        .unwrap();
+        register_file_view(&conn, "users_csv", &[path.to_string_lossy().to_string()], FileViewFormat::Csv, true)
+            .unwrap();
+
+        let summary: Vec<(String, String, TableType, Option<i64>)> = list_tables(&conn, false, false, &InterruptGroup::default())
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.database, t.name, t.table_type, t.row_count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("memory".to_string(), "tokyo_users".to_string(), TableType::View, None),
+                ("memory".to_string(), "users".to_string(), TableType::Table, Some(10)),
+                ("temp".to_string(), "users_csv".to_string(), TableType::FileView, None),
+            ]
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_exact_counts_and_storage_size() {
+        let dir = std::env::temp_dir().join(format!("duckdb-viewer-stats-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let conn = Connection::open(dir.join("stats.duckdb")).unwrap();
+        conn.execute_batch(
+            "CREATE SCHEMA \"odd schema\";
+             CREATE TABLE \"odd schema\".\"it's\" AS SELECT range AS i FROM range(100000);
+             DELETE FROM \"odd schema\".\"it's\" WHERE i < 10;
+             CHECKPOINT;
+             CREATE TEMP TABLE scratch AS SELECT 1 AS i;",
+        )
+        .unwrap();
+        for i in 0..20 {
+            conn.execute_batch(&format!("CREATE TABLE t{0} AS SELECT range AS i FROM range({0})", i))
+                .unwrap();
+        }
+
+        let estimated = list_tables(&conn, false, true, &InterruptGroup::default()).unwrap();
+        let odd = estimated.iter().find(|t| t.name == "it's").unwrap();
+        assert_eq!(odd.row_count, Some(100000));
+        assert!(!odd.row_count_exact);
+        assert!(odd.size_bytes.unwrap() > 0);
+
+        let exact = list_tables(&conn, true, true, &InterruptGroup::default()).unwrap();
+        let odd = exact.iter().find(|t| t.name == "it's").unwrap();
+        assert_eq!(odd.row_count, Some(99990));
+        assert!(odd.row_count_exact);
+        for i in 0..20 {
+            let table = exact.iter().find(|t| t.name == format!("t{}", i)).unwrap();
+            assert_eq!(table.row_count, Some(i));
+            // Not checkpointed yet, so still only in the WAL and in memory
+            assert_eq!(table.size_bytes, if i == 0 { Some(0) } else { None });
+        }
+        let scratch = exact.iter().find(|t| t.name == "scratch").unwrap();
+        assert_eq!((scratch.row_count, scratch.size_bytes), (Some(1), None));
+
+        // Sizes are only looked up on request
+        let without_sizes = list_tables(&conn, true, false, &InterruptGroup::default()).unwrap();
+        assert!(without_sizes.iter().all(|t| t.size_bytes.is_none()));
+
+        // Workers of an interrupted query start nothing
+        let interrupts = InterruptGroup::default();
+        interrupts.interrupt();
+        assert!(matches!(list_tables(&conn, true, false, &interrupts), Err(AppError::Cancelled)));
+
+        drop(conn);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,334 @@
+use duckdb::arrow::array::{ArrayRef, AsArray};
+use duckdb::arrow::datatypes::{DataType, Field, TimeUnit};
+use duckdb::arrow::record_batch::RecordBatch;
+use duckdb::core::{LogicalTypeHandle, LogicalTypeId};
+use duckdb::Statement;
+
+use crate::quote::{quote_identifier, quote_literal};
+
+/// Arrow field metadata naming the DuckDB type of a column Arrow has no type
+/// for, such as UHUGEINT or BIGNUM
+const EXTENSION_METADATA: &str = "ARROW:extension:metadata";
+
+/// DuckDB type names of every result column of `stmt`, e.g. `DECIMAL(10,2)`,
+/// `TIMESTAMP WITH TIME ZONE` or `STRUCT(a INTEGER)`. Taken from statement
+/// metadata, so zero-row results are covered too.
+///
+/// duckdb-rs exposes the logical types of STRUCT, UNION and ARRAY members but
+/// not LIST and MAP element types or ENUM values. Element types are named
+/// from the result's Arrow types instead, which carry some DuckDB types as
+/// others (a UUID element reads as VARCHAR, a HUGEINT one as DECIMAL(38,0)),
+/// and ENUM values are read from the dictionary of `chunk`, a chunk of the
+/// result, when there is one.
+pub fn column_types(stmt: &Statement<'_>, chunk: Option<&RecordBatch>) -> Vec<String> {
+    (0..stmt.column_count())
+        .map(|i| {
+            let data = chunk.and_then(|chunk| chunk.columns().get(i));
+            logical_type_name(&stmt.column_logical_type(i), &stmt.column_type(i), data)
+        })
+        .collect()
+}
+
+/// Render a DuckDB logical type the way DuckDB prints it. LIST and MAP
+/// element types and anything unrecognised are rendered from the column's
+/// Arrow type, and ENUM values from `data`, the column's values, if given.
+fn logical_type_name(logical: &LogicalTypeHandle, arrow: &DataType, data: Option<&ArrayRef>) -> String {
+    if let Some(alias) = logical.get_alias() {
+        return alias;
+    }
+
+    match logical.id() {
+        LogicalTypeId::Decimal => format!("DECIMAL({},{})", logical.decimal_width(), logical.decimal_scale()),
+        LogicalTypeId::Struct => {
+            let fields = struct_fields(arrow);
+            let members = (0..logical.num_children())
+                .map(|i| {
+                    let child_arrow = fields.get(i).map(|f| f.data_type()).unwrap_or(&DataType::Null);
+                    let child_data = data.and_then(|data| data.as_struct_opt()).map(|data| data.column(i));
+                    format!(
+                        "{} {}",
+                        quote_field_name(&logical.child_name(i)),
+                        logical_type_name(&logical.child(i), child_arrow, child_data)
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!("STRUCT({})", members.join(", "))
+        }
+        LogicalTypeId::Union => {
+            let members = (0..logical.num_children())
+                .map(|i| {
+                    let (child_arrow, child_data) = match arrow {
+                        DataType::Union(fields, _) => match fields.iter().nth(i) {
+                            Some((type_id, field)) => {
+                                let child_data = data.and_then(|data| data.as_union_opt()).map(|data| data.child(type_id));
+                                (field.data_type(), child_data)
+                            }
+                            None => (&DataType::Null, None),
+                        },
+                        _ => (&DataType::Null, None),
+                    };
+                    format!(
+                        "{} {}",
+                        quote_field_name(&logical.child_name(i)),
+                        logical_type_name(&logical.child(i), child_arrow, child_data)
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!("UNION({})", members.join(", "))
+        }
+        LogicalTypeId::Array => match arrow {
+            DataType::FixedSizeList(field, size) => {
+                let child_data = data.and_then(|data| data.as_fixed_size_list_opt()).map(|data| data.values());
+                format!("{}[{}]", logical_type_name(&logical.child(0), field.data_type(), child_data), size)
+            }
+            _ => arrow_type_name(arrow, data),
+        },
+        LogicalTypeId::Enum => enum_type_name(data),
+        id => scalar_type_name(id).map_or_else(|| arrow_type_name(arrow, data), str::to_string),
+    }
+}
+
+/// Name of a type that has no parameters or element types
+fn scalar_type_name(id: LogicalTypeId) -> Option<&'static str> {
+    let name = match id {
+        LogicalTypeId::Boolean => "BOOLEAN",
+        LogicalTypeId::Tinyint => "TINYINT",
+        LogicalTypeId::Smallint => "SMALLINT",
+        LogicalTypeId::Integer => "INTEGER",
+        LogicalTypeId::Bigint => "BIGINT",
+        LogicalTypeId::Hugeint => "HUGEINT",
+        LogicalTypeId::UTinyint => "UTINYINT",
+        LogicalTypeId::USmallint => "USMALLINT",
+        LogicalTypeId::UInteger => "UINTEGER",
+        LogicalTypeId::UBigint => "UBIGINT",
+        LogicalTypeId::UHugeint => "UHUGEINT",
+        LogicalTypeId::Float => "FLOAT",
+        LogicalTypeId::Double => "DOUBLE",
+        LogicalTypeId::Varchar => "VARCHAR",
+        LogicalTypeId::Blob => "BLOB",
+        LogicalTypeId::Bit => "BIT",
+        LogicalTypeId::Uuid => "UUID",
+        LogicalTypeId::Bignum => "BIGNUM",
+        LogicalTypeId::Date => "DATE",
+        LogicalTypeId::Time | LogicalTypeId::TimeNs => "TIME",
+        LogicalTypeId::TimeTZ => "TIME WITH TIME ZONE",
+        LogicalTypeId::Timestamp => "TIMESTAMP",
+        LogicalTypeId::TimestampS => "TIMESTAMP_S",
+        LogicalTypeId::TimestampMs => "TIMESTAMP_MS",
+        LogicalTypeId::TimestampNs => "TIMESTAMP_NS",
+        LogicalTypeId::TimestampTZ => "TIMESTAMP WITH TIME ZONE",
+        LogicalTypeId::Interval => "INTERVAL",
+        LogicalTypeId::Enum => "ENUM",
+        LogicalTypeId::SqlNull => "NULL",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Render an Arrow type with the name of the DuckDB type it comes from,
+/// reading ENUM values from `data`, values of that type, if given
+fn arrow_type_name(arrow: &DataType, data: Option<&ArrayRef>) -> String {
+    let name = match arrow {
+        DataType::Null => "NULL",
+        DataType::Boolean => "BOOLEAN",
+        DataType::Int8 => "TINYINT",
+        DataType::Int16 => "SMALLINT",
+        DataType::Int32 => "INTEGER",
+        DataType::Int64 => "BIGINT",
+        DataType::UInt8 => "UTINYINT",
+        DataType::UInt16 => "USMALLINT",
+        DataType::UInt32 => "UINTEGER",
+        DataType::UInt64 => "UBIGINT",
+        DataType::Float16 | DataType::Float32 => "FLOAT",
+        DataType::Float64 => "DOUBLE",
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => "VARCHAR",
+        DataType::Binary | DataType::LargeBinary | DataType::BinaryView | DataType::FixedSizeBinary(_) => {
+            "BLOB"
+        }
+        DataType::Date32 | DataType::Date64 => "DATE",
+        DataType::Time32(_) | DataType::Time64(_) => "TIME",
+        DataType::Timestamp(_, Some(_)) => "TIMESTAMP WITH TIME ZONE",
+        DataType::Timestamp(TimeUnit::Second, None) => "TIMESTAMP_S",
+        DataType::Timestamp(TimeUnit::Millisecond, None) => "TIMESTAMP_MS",
+        DataType::Timestamp(TimeUnit::Microsecond, None) => "TIMESTAMP",
+        DataType::Timestamp(TimeUnit::Nanosecond, None) => "TIMESTAMP_NS",
+        DataType::Interval(_) | DataType::Duration(_) => "INTERVAL",
+        DataType::Dictionary(_, _) => return enum_type_name(data),
+        DataType::Decimal128(width, scale) | DataType::Decimal256(width, scale) => {
+            return format!("DECIMAL({},{})", width, scale);
+        }
+        DataType::List(field) => {
+            let values = data.and_then(|data| data.as_list_opt::<i32>()).map(|data| data.values());
+            return format!("{}[]", field_type_name(field, values));
+        }
+        DataType::LargeList(field) => {
+            let values = data.and_then(|data| data.as_list_opt::<i64>()).map(|data| data.values());
+            return format!("{}[]", field_type_name(field, values));
+        }
+        DataType::FixedSizeList(field, size) => {
+            let values = data.and_then(|data| data.as_fixed_size_list_opt()).map(|data| data.values());
+            return format!("{}[{}]", field_type_name(field, values), size);
+        }
+        DataType::Struct(fields) => {
+            let data = data.and_then(|data| data.as_struct_opt());
+            let members = fields
+                .iter()
+                .enumerate()
+                .map(|(i, f)| (f.as_ref(), data.map(|data| data.column(i))))
+                .collect::<Vec<_>>();
+            return format!("STRUCT({})", arrow_members(members));
+        }
+        DataType::Union(fields, _) => {
+            let data = data.and_then(|data| data.as_union_opt());
+            let members = fields
+                .iter()
+                .map(|(type_id, f)| (f.as_ref(), data.map(|data| data.child(type_id))))
+                .collect::<Vec<_>>();
+            return format!("UNION({})", arrow_members(members));
+        }
+        DataType::Map(entries, _) => {
+            let kv = struct_fields(entries.data_type());
+            let data = data.and_then(|data| data.as_map_opt());
+            let key = kv.first().map(|f| field_type_name(f, data.map(|data| data.keys()))).unwrap_or_default();
+            let value = kv.get(1).map(|f| field_type_name(f, data.map(|data| data.values()))).unwrap_or_default();
+            return format!("MAP({}, {})", key, value);
+        }
+        other => return other.to_string(),
+    };
+
+    name.to_string()
+}
+
+/// Render the type of a nested Arrow field, using the DuckDB type name its
+/// metadata gives for a type Arrow has no counterpart for
+fn field_type_name(field: &Field, data: Option<&ArrayRef>) -> String {
+    let duckdb_name = field
+        .metadata()
+        .get(EXTENSION_METADATA)
+        .and_then(|metadata| serde_json::from_str::<serde_json::Value>(metadata).ok())
+        .and_then(|metadata| metadata["type_name"].as_str().map(str::to_uppercase));
+    duckdb_name.unwrap_or_else(|| arrow_type_name(field.data_type(), data))
+}
+
+/// `ENUM('a', 'b')` from the dictionary of `data`, the values of an ENUM
+/// column, which DuckDB fills with every value of the type; just `ENUM`
+/// without any
+fn enum_type_name(data: Option<&ArrayRef>) -> String {
+    let dictionary = data
+        .and_then(|data| data.as_any_dictionary_opt())
+        .and_then(|data| data.values().as_string_opt::<i32>().cloned());
+    match dictionary {
+        Some(values) => {
+            let values = values.iter().map(|v| quote_literal(v.unwrap_or_default())).collect::<Vec<_>>();
+            format!("ENUM({})", values.join(", "))
+        }
+        None => "ENUM".to_string(),
+    }
+}
+
+fn arrow_members(fields: Vec<(&Field, Option<&ArrayRef>)>) -> String {
+    fields
+        .into_iter()
+        .map(|(f, data)| format!("{} {}", quote_field_name(f.name()), field_type_name(f, data)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn struct_fields(arrow: &DataType) -> Vec<&Field> {
+    match arrow {
+        DataType::Struct(fields) => fields.iter().map(|f| f.as_ref()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Quote a STRUCT/UNION member name unless it is a plain lowercase identifier
+fn quote_field_name(name: &str) -> String {
+    let plain = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    if plain {
+        name.to_string()
+    } else {
+        quote_identifier(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use duckdb::Connection;
+
+    fn types_of(sql: &str) -> Vec<String> {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TYPE mood AS ENUM ('happy', 'sad')").unwrap();
+        let mut stmt = conn.prepare(sql).unwrap();
+        let chunks = stmt.query_arrow([]).unwrap().collect::<Vec<_>>();
+        column_types(&stmt, chunks.first())
+    }
+
+    #[test]
+    fn test_scalar_types() {
+        let types = types_of(
+            "SELECT 1::DECIMAL(10,2), TIMESTAMPTZ '2024-01-01 00:00:00+00', DATE '2024-01-01', \
+             'x'::VARCHAR, 1::HUGEINT, 1::UBIGINT, gen_random_uuid(), INTERVAL 1 DAY",
+        );
+        assert_eq!(
+            types,
+            vec![
+                "DECIMAL(10,2)",
+                "TIMESTAMP WITH TIME ZONE",
+                "DATE",
+                "VARCHAR",
+                "HUGEINT",
+                "UBIGINT",
+                "UUID",
+                "INTERVAL",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_types() {
+        let types = types_of(
+            "SELECT {'a': 1}, [1, 2], [1, 2, 3]::INTEGER[3], MAP {'k': 1.5}, {'My Field': 'x'}",
+        );
+        assert_eq!(
+            types,
+            vec![
+                "STRUCT(a INTEGER)",
+                "INTEGER[]",
+                "INTEGER[3]",
+                "MAP(VARCHAR, DECIMAL(2,1))",
+                "STRUCT(\"My Field\" VARCHAR)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_map_and_enum_types() {
+        let types = types_of(
+            "SELECT ['a'], MAP {'k': 1.5}, 'sad'::mood, ['x'::ENUM('x', 'y')], {'ids': [1::UHUGEINT]}, [1::BIGNUM]",
+        );
+        assert_eq!(
+            types,
+            vec![
+                "VARCHAR[]",
+                "MAP(VARCHAR, DECIMAL(2,1))",
+                "ENUM('happy', 'sad')",
+                "ENUM('x', 'y')[]",
+                "STRUCT(ids UHUGEINT[])",
+                "BIGNUM[]",
+            ]
+        );
+
+        // Without rows there is no dictionary to read ENUM values from
+        assert_eq!(types_of("SELECT 'sad'::mood WHERE false"), vec!["ENUM"]);
+    }
+
+    #[test]
+    fn test_zero_row_result_keeps_types() {
+        let types = types_of("SELECT 1::DECIMAL(10,2) AS price, NOW() AS ts WHERE false");
+        assert_eq!(types, vec!["DECIMAL(10,2)", "TIMESTAMP WITH TIME ZONE"]);
+    }
+}
@@ -0,0 +1,768 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, Utc};
+use duckdb::arrow::array::{Array, ArrayRef, AsArray, UnionArray};
+use duckdb::arrow::datatypes::{
+    DataType, Date32Type, Decimal128Type, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+    IntervalMonthDayNanoType, IntervalUnit, Time64MicrosecondType, Time64NanosecondType, TimeUnit as ArrowTimeUnit,
+    TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType, TimestampSecondType, UInt16Type,
+    UInt32Type, UInt64Type, UInt8Type,
+};
+use duckdb::arrow::record_batch::RecordBatch;
+use duckdb::arrow::util::display::array_value_to_string;
+use duckdb::types::{EnumType, ListType, TimeUnit, Value, ValueRef};
+use rust_decimal::Decimal;
+use serde_json::{Map, Value as JsonValue};
+
+use crate::models::ResultEncoding;
+use crate::settings::{DisplaySettings, DisplayTimezone};
+
+/// TIMESTAMP rendering; fractional seconds are only shown when present
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+/// Days from 0001-01-01 to 1970-01-01, the epoch of DuckDB's DATE
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// Column type name of TIMESTAMPTZ in `types::column_types`
+const TIMESTAMPTZ: &str = "TIMESTAMP WITH TIME ZONE";
+
+/// Arrow type of a nested member whose type is not known, such as the value
+/// of a UNION inside a LIST
+static UNKNOWN_TYPE: DataType = DataType::Null;
+
+/// How the values of one query result are converted to JSON
+#[derive(Debug, Clone, Default)]
+pub struct ValueFormat {
+    pub timezone: DisplayTimezone,
+    pub encoding: ResultEncoding,
+}
+
+impl ValueFormat {
+    pub fn new(display: &DisplaySettings, encoding: ResultEncoding) -> Self {
+        Self {
+            timezone: display.timezone,
+            encoding,
+        }
+    }
+}
+
+/// Convert a value of a result column to JSON. `column_type` is the DuckDB
+/// type name from `types::column_types`; BIT, UHUGEINT and TIMESTAMPTZ
+/// values arrive as plain bytes and naive timestamps and can only be told
+/// apart by it.
+pub fn column_value_to_json(
+    val_ref: ValueRef,
+    column_type: &str,
+    format: &ValueFormat,
+) -> JsonValue {
+    match val_ref {
+        ValueRef::Blob(bytes) if column_type == "BIT" => JsonValue::from(bit_string(bytes)),
+        ValueRef::Blob(bytes) if column_type == "UHUGEINT" => match <[u8; 16]>::try_from(bytes) {
+            Ok(bytes) => {
+                let value = u128::from_le_bytes(bytes).to_string();
+                match format.encoding {
+                    ResultEncoding::Plain => JsonValue::from(value),
+                    ResultEncoding::Typed => typed_number("UHUGEINT", JsonValue::from(value)),
+                }
+            }
+            Err(_) => encode_value_ref(val_ref, format),
+        },
+        ValueRef::Timestamp(unit, value) if column_type == TIMESTAMPTZ => {
+            JsonValue::from(timestamptz_string(unit, value, format.timezone))
+        }
+        _ => encode_value_ref(val_ref, format),
+    }
+}
+
+/// Convert DuckDB ValueRef to JSON Value
+pub fn value_ref_to_json(val_ref: ValueRef) -> JsonValue {
+    encode_value_ref(val_ref, &ValueFormat::default())
+}
+
+fn encode_value_ref(val_ref: ValueRef, format: &ValueFormat) -> JsonValue {
+    if format.encoding == ResultEncoding::Typed {
+        if let Some(typed) = typed_numeric(val_ref) {
+            return typed;
+        }
+    }
+
+    match val_ref {
+        ValueRef::Null => JsonValue::Null,
+        ValueRef::Boolean(b) => JsonValue::from(b),
+        ValueRef::TinyInt(i) => JsonValue::from(i),
+        ValueRef::SmallInt(i) => JsonValue::from(i),
+        ValueRef::Int(i) => JsonValue::from(i),
+        ValueRef::BigInt(i) => JsonValue::from(i),
+        ValueRef::HugeInt(i) => JsonValue::from(i.to_string()),
+        ValueRef::UTinyInt(i) => JsonValue::from(i),
+        ValueRef::USmallInt(i) => JsonValue::from(i),
+        ValueRef::UInt(i) => JsonValue::from(i),
+        ValueRef::UBigInt(i) => JsonValue::from(i),
+        ValueRef::Float(f) => serde_json::Number::from_f64(f as f64)
+            .map(JsonValue::Number)
+            .unwrap_or(JsonValue::Null),
+        ValueRef::Double(f) => serde_json::Number::from_f64(f)
+            .map(JsonValue::Number)
+            .unwrap_or(JsonValue::Null),
+        ValueRef::Decimal(d) => JsonValue::from(d.to_string()),
+        ValueRef::Text(bytes) => JsonValue::from(String::from_utf8_lossy(bytes).to_string()),
+        ValueRef::Blob(bytes) => JsonValue::from(format!("<{} bytes>", bytes.len())),
+        ValueRef::Timestamp(unit, value) => {
+            let (secs, nanos) = split_seconds(unit, value);
+            match DateTime::from_timestamp(secs, nanos) {
+                Some(dt) => JsonValue::from(dt.naive_utc().format(DATETIME_FORMAT).to_string()),
+                None => JsonValue::from(special_value(value)),
+            }
+        }
+        ValueRef::Date32(days) => {
+            match NaiveDate::from_num_days_from_ce_opt(days.saturating_add(UNIX_EPOCH_DAYS_FROM_CE))
+            {
+                Some(date) => JsonValue::from(date.format("%Y-%m-%d").to_string()),
+                None => JsonValue::from(special_value(days.into())),
+            }
+        }
+        ValueRef::Time64(unit, value) => {
+            let (secs, nanos) = split_seconds(unit, value);
+            match u32::try_from(secs)
+                .ok()
+                .and_then(|secs| NaiveTime::from_num_seconds_from_midnight_opt(secs, nanos))
+            {
+                Some(time) => JsonValue::from(time.format("%H:%M:%S%.f").to_string()),
+                None => JsonValue::from(value.to_string()),
+            }
+        }
+        ValueRef::Interval { months, days, nanos } => JsonValue::from(iso_duration(months, days, nanos)),
+        ValueRef::Union(column, idx) => union_to_json(column, idx, val_ref, format),
+        ValueRef::Enum(..) => value_to_json(&val_ref.to_owned(), &UNKNOWN_TYPE, format),
+        ValueRef::List(ListType::Regular(list), _) => value_to_json(&val_ref.to_owned(), list.data_type(), format),
+        ValueRef::List(ListType::Large(list), _) => value_to_json(&val_ref.to_owned(), list.data_type(), format),
+        ValueRef::Array(array, _) => value_to_json(&val_ref.to_owned(), array.data_type(), format),
+        ValueRef::Struct(array, _) => value_to_json(&val_ref.to_owned(), array.data_type(), format),
+        ValueRef::Map(array, _) => value_to_json(&val_ref.to_owned(), array.data_type(), format),
+    }
+}
+
+/// Tag a number with its DuckDB type for [`ResultEncoding::Typed`]. Integers
+/// of 64 bits and wider and DECIMALs, which JavaScript numbers cannot hold
+/// exactly, are given as strings, as are non-finite floats.
+fn typed_numeric(val_ref: ValueRef) -> Option<JsonValue> {
+    let (type_name, value) = match val_ref {
+        ValueRef::TinyInt(i) => ("TINYINT", JsonValue::from(i)),
+        ValueRef::SmallInt(i) => ("SMALLINT", JsonValue::from(i)),
+        ValueRef::Int(i) => ("INTEGER", JsonValue::from(i)),
+        ValueRef::BigInt(i) => ("BIGINT", JsonValue::from(i.to_string())),
+        ValueRef::HugeInt(i) => ("HUGEINT", JsonValue::from(i.to_string())),
+        ValueRef::UTinyInt(i) => ("UTINYINT", JsonValue::from(i)),
+        ValueRef::USmallInt(i) => ("USMALLINT", JsonValue::from(i)),
+        ValueRef::UInt(i) => ("UINTEGER", JsonValue::from(i)),
+        ValueRef::UBigInt(i) => ("UBIGINT", JsonValue::from(i.to_string())),
+        ValueRef::Float(f) => ("FLOAT", float_value(f as f64)),
+        ValueRef::Double(f) => ("DOUBLE", float_value(f)),
+        ValueRef::Decimal(d) => ("DECIMAL", JsonValue::from(d.to_string())),
+        _ => return None,
+    };
+    Some(typed_number(type_name, value))
+}
+
+fn typed_number(type_name: &str, value: JsonValue) -> JsonValue {
+    serde_json::json!({ "type": type_name, "value": value })
+}
+
+fn float_value(f: f64) -> JsonValue {
+    serde_json::Number::from_f64(f)
+        .map(JsonValue::Number)
+        .unwrap_or_else(|| JsonValue::from(f.to_string()))
+}
+
+/// Convert an owned value, as duckdb builds them for the members of nested
+/// types. `data_type` is its Arrow type, which tells TIMESTAMPTZ members,
+/// shown in the display time zone, from naive timestamps.
+fn value_to_json(value: &Value, data_type: &DataType, format: &ValueFormat) -> JsonValue {
+    match value {
+        Value::List(items) | Value::Array(items) => {
+            let item_type = member_type(data_type);
+            JsonValue::Array(items.iter().map(|item| value_to_json(item, item_type, format)).collect())
+        }
+        Value::Struct(fields) => JsonValue::Object(
+            fields
+                .iter()
+                .enumerate()
+                .map(|(i, (name, field))| (name.clone(), value_to_json(field, field_type(data_type, i), format)))
+                .collect(),
+        ),
+        Value::Map(entries) => {
+            let entry_type = member_type(data_type);
+            let (key_type, value_type) = (field_type(entry_type, 0), field_type(entry_type, 1));
+            JsonValue::Object(
+                entries
+                    .iter()
+                    .map(|(key, entry)| (map_key(key, key_type, format), value_to_json(entry, value_type, format)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Enum(label) => JsonValue::from(label.as_str()),
+        // The member name is only known at the top level, see `union_to_json`
+        Value::Union(inner) => value_to_json(inner, &UNKNOWN_TYPE, format),
+        Value::Timestamp(unit, value) if matches!(data_type, DataType::Timestamp(_, Some(_))) => {
+            JsonValue::from(timestamptz_string(*unit, *value, format.timezone))
+        }
+        scalar => encode_value_ref(ValueRef::from(scalar), format),
+    }
+}
+
+/// Type of the members of a LIST or ARRAY, or of the key-value STRUCT
+/// entries of a MAP
+fn member_type(data_type: &DataType) -> &DataType {
+    match data_type {
+        DataType::List(field)
+        | DataType::LargeList(field)
+        | DataType::FixedSizeList(field, _)
+        | DataType::Map(field, _) => field.data_type(),
+        _ => &UNKNOWN_TYPE,
+    }
+}
+
+/// Type of the field at `index` of a STRUCT
+fn field_type(data_type: &DataType, index: usize) -> &DataType {
+    match data_type {
+        DataType::Struct(fields) => fields.get(index).map_or(&UNKNOWN_TYPE, |field| field.data_type()),
+        _ => &UNKNOWN_TYPE,
+    }
+}
+
+/// JSON object keys must be strings, so MAP keys of other types are rendered
+/// as their plain JSON text
+fn map_key(key: &Value, key_type: &DataType, format: &ValueFormat) -> String {
+    let plain = ValueFormat {
+        encoding: ResultEncoding::Plain,
+        ..format.clone()
+    };
+    match value_to_json(key, key_type, &plain) {
+        JsonValue::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+/// Render a UNION as `{"tag": <member name>, "value": <member value>}`
+fn union_to_json(column: &ArrayRef, idx: usize, val_ref: ValueRef, format: &ValueFormat) -> JsonValue {
+    let member = column.as_any().downcast_ref::<UnionArray>().and_then(|union| {
+        let type_id = union.type_id(idx);
+        match union.data_type() {
+            DataType::Union(fields, _) => fields
+                .iter()
+                .find(|(id, _)| *id == type_id)
+                .map(|(_, field)| (field.name().clone(), field.data_type())),
+            _ => None,
+        }
+    });
+
+    match member {
+        Some((tag, member_type)) => {
+            let value = value_to_json(&val_ref.to_owned(), member_type, format);
+            serde_json::json!({ "tag": tag, "value": value })
+        }
+        None => value_to_json(&val_ref.to_owned(), &UNKNOWN_TYPE, format),
+    }
+}
+
+/// Split a count of `unit` since the epoch (or midnight) into whole seconds
+/// and nanoseconds, rounding down so values before the epoch keep a
+/// non-negative fraction
+fn split_seconds(unit: TimeUnit, value: i64) -> (i64, u32) {
+    let per_second: i64 = match unit {
+        TimeUnit::Second => 1,
+        TimeUnit::Millisecond => 1_000,
+        TimeUnit::Microsecond => 1_000_000,
+        TimeUnit::Nanosecond => 1_000_000_000,
+    };
+    let nanos = value.rem_euclid(per_second) * (1_000_000_000 / per_second);
+    (value.div_euclid(per_second), nanos as u32)
+}
+
+/// DuckDB's `infinity` and `-infinity` are the extreme values of the
+/// underlying integer; anything else out of chrono's range is shown raw
+fn special_value(value: i64) -> String {
+    if value == i64::MAX || value == i32::MAX as i64 {
+        "infinity".to_string()
+    } else if value == -i64::MAX || value == -(i32::MAX as i64) {
+        "-infinity".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render a TIMESTAMPTZ instant in the display time zone, with its offset
+fn timestamptz_string(unit: TimeUnit, value: i64, timezone: DisplayTimezone) -> String {
+    let (secs, nanos) = split_seconds(unit, value);
+    let Some(utc) = DateTime::from_timestamp(secs, nanos) else {
+        return special_value(value);
+    };
+
+    let format = "%Y-%m-%d %H:%M:%S%.f%:z";
+    match timezone {
+        DisplayTimezone::Local => utc.with_timezone(&Local).format(format).to_string(),
+        DisplayTimezone::Utc => utc.with_timezone(&Utc).format(format).to_string(),
+        DisplayTimezone::Fixed(offset) => utc.with_timezone(&offset).format(format).to_string(),
+    }
+}
+
+/// Render an INTERVAL as an ISO-8601 duration such as `P1Y2M3DT4H5M6.5S`.
+/// DuckDB keeps months, days and sub-day time separately and each may be
+/// negative, so every component carries its own sign.
+fn iso_duration(months: i32, days: i32, nanos: i64) -> String {
+    let mut date = String::new();
+    for (amount, designator) in [(months / 12, 'Y'), (months % 12, 'M'), (days, 'D')] {
+        if amount != 0 {
+            date.push_str(&format!("{}{}", amount, designator));
+        }
+    }
+
+    let sign = if nanos < 0 { "-" } else { "" };
+    let total = nanos.unsigned_abs();
+    let hours = total / 3_600_000_000_000;
+    let minutes = total / 60_000_000_000 % 60;
+    let seconds = total / 1_000_000_000 % 60;
+    let fraction = total % 1_000_000_000;
+
+    let mut time = String::new();
+    if hours != 0 {
+        time.push_str(&format!("{}{}H", sign, hours));
+    }
+    if minutes != 0 {
+        time.push_str(&format!("{}{}M", sign, minutes));
+    }
+    if fraction != 0 {
+        let digits = format!("{:09}", fraction);
+        time.push_str(&format!(
+            "{}{}.{}S",
+            sign,
+            seconds,
+            digits.trim_end_matches('0')
+        ));
+    } else if seconds != 0 {
+        time.push_str(&format!("{}{}S", sign, seconds));
+    }
+
+    match (date.is_empty(), time.is_empty()) {
+        (true, true) => "PT0S".to_string(),
+        (_, true) => format!("P{}", date),
+        _ => format!("P{}T{}", date, time),
+    }
+}
+
+/// DuckDB stores a BIT as a padding-bit count followed by the bits, most
+/// significant first; the leading padding bits of the first data byte are unused
+fn bit_string(bytes: &[u8]) -> String {
+    let Some((&padding, data)) = bytes.split_first() else {
+        return String::new();
+    };
+
+    data.iter()
+        .flat_map(|byte| (0..8).rev().map(move |bit| if byte >> bit & 1 == 1 { '1' } else { '0' }))
+        .skip(padding as usize)
+        .collect()
+}
+
+/// Convert row `row` of a chunk of a query result, one value per column;
+/// `column_types` are the columns' names from `types::column_types`
+pub fn chunk_row_to_json(chunk: &RecordBatch, row: usize, column_types: &[String], format: &ValueFormat) -> Vec<JsonValue> {
+    chunk
+        .columns()
+        .iter()
+        .zip(column_types)
+        .map(|(column, column_type)| arrow_value_to_json(column, row, column_type, format))
+        .collect()
+}
+
+/// Convert the value at `row` of a result column held in an Arrow array.
+/// Arrow types with no `ValueRef` counterpart are rendered as text by Arrow
+/// itself, so an unusual column never fails the rest of the rows.
+fn arrow_value_to_json(column: &ArrayRef, row: usize, column_type: &str, format: &ValueFormat) -> JsonValue {
+    match arrow_value_ref(column, row) {
+        Some(val_ref) => column_value_to_json(val_ref, column_type, format),
+        None => array_value_to_string(column, row).map_or(JsonValue::Null, JsonValue::from),
+    }
+}
+
+/// The value at `row` of an Arrow array, read the way duckdb-rs's
+/// `Row::get_ref` reads it, or None for an Arrow type it has no `ValueRef` for
+fn arrow_value_ref(column: &ArrayRef, row: usize) -> Option<ValueRef<'_>> {
+    if column.is_null(row) {
+        return Some(ValueRef::Null);
+    }
+
+    let value = match column.data_type() {
+        DataType::Null => ValueRef::Null,
+        DataType::Utf8 => ValueRef::Text(column.as_string::<i32>().value(row).as_bytes()),
+        DataType::LargeUtf8 => ValueRef::Text(column.as_string::<i64>().value(row).as_bytes()),
+        DataType::Binary => ValueRef::Blob(column.as_binary::<i32>().value(row)),
+        DataType::LargeBinary => ValueRef::Blob(column.as_binary::<i64>().value(row)),
+        DataType::FixedSizeBinary(_) => ValueRef::Blob(column.as_fixed_size_binary().value(row)),
+        DataType::Boolean => ValueRef::Boolean(column.as_boolean().value(row)),
+        DataType::Int8 => ValueRef::TinyInt(column.as_primitive::<Int8Type>().value(row)),
+        DataType::Int16 => ValueRef::SmallInt(column.as_primitive::<Int16Type>().value(row)),
+        DataType::Int32 => ValueRef::Int(column.as_primitive::<Int32Type>().value(row)),
+        DataType::Int64 => ValueRef::BigInt(column.as_primitive::<Int64Type>().value(row)),
+        DataType::UInt8 => ValueRef::UTinyInt(column.as_primitive::<UInt8Type>().value(row)),
+        DataType::UInt16 => ValueRef::USmallInt(column.as_primitive::<UInt16Type>().value(row)),
+        DataType::UInt32 => ValueRef::UInt(column.as_primitive::<UInt32Type>().value(row)),
+        DataType::UInt64 => ValueRef::UBigInt(column.as_primitive::<UInt64Type>().value(row)),
+        DataType::Float32 => ValueRef::Float(column.as_primitive::<Float32Type>().value(row)),
+        DataType::Float64 => ValueRef::Double(column.as_primitive::<Float64Type>().value(row)),
+        // HUGEINT arrives as DECIMAL(38,0)
+        DataType::Decimal128(_, 0) => ValueRef::HugeInt(column.as_primitive::<Decimal128Type>().value(row)),
+        DataType::Decimal128(_, scale) => ValueRef::Decimal(Decimal::from_i128_with_scale(
+            column.as_primitive::<Decimal128Type>().value(row),
+            *scale as u32,
+        )),
+        DataType::Timestamp(ArrowTimeUnit::Second, _) => {
+            ValueRef::Timestamp(TimeUnit::Second, column.as_primitive::<TimestampSecondType>().value(row))
+        }
+        DataType::Timestamp(ArrowTimeUnit::Millisecond, _) => ValueRef::Timestamp(
+            TimeUnit::Millisecond,
+            column.as_primitive::<TimestampMillisecondType>().value(row),
+        ),
+        DataType::Timestamp(ArrowTimeUnit::Microsecond, _) => ValueRef::Timestamp(
+            TimeUnit::Microsecond,
+            column.as_primitive::<TimestampMicrosecondType>().value(row),
+        ),
+        DataType::Timestamp(ArrowTimeUnit::Nanosecond, _) => ValueRef::Timestamp(
+            TimeUnit::Nanosecond,
+            column.as_primitive::<TimestampNanosecondType>().value(row),
+        ),
+        DataType::Date32 => ValueRef::Date32(column.as_primitive::<Date32Type>().value(row)),
+        DataType::Time64(ArrowTimeUnit::Microsecond) => ValueRef::Time64(
+            TimeUnit::Microsecond,
+            column.as_primitive::<Time64MicrosecondType>().value(row),
+        ),
+        DataType::Time64(ArrowTimeUnit::Nanosecond) => ValueRef::Time64(
+            TimeUnit::Nanosecond,
+            column.as_primitive::<Time64NanosecondType>().value(row),
+        ),
+        DataType::Interval(IntervalUnit::MonthDayNano) => {
+            let interval = column.as_primitive::<IntervalMonthDayNanoType>().value(row);
+            ValueRef::Interval {
+                months: interval.months,
+                days: interval.days,
+                nanos: interval.nanoseconds,
+            }
+        }
+        DataType::List(_) => ValueRef::List(ListType::Regular(column.as_list::<i32>()), row),
+        DataType::LargeList(_) => ValueRef::List(ListType::Large(column.as_list::<i64>()), row),
+        DataType::Dictionary(key, _) => {
+            let values = match key.as_ref() {
+                DataType::UInt8 => EnumType::UInt8(column.as_dictionary::<UInt8Type>()),
+                DataType::UInt16 => EnumType::UInt16(column.as_dictionary::<UInt16Type>()),
+                DataType::UInt32 => EnumType::UInt32(column.as_dictionary::<UInt32Type>()),
+                _ => return None,
+            };
+            ValueRef::Enum(values, row)
+        }
+        DataType::Struct(_) => ValueRef::Struct(column.as_struct(), row),
+        DataType::Map(..) => ValueRef::Map(column.as_map(), row),
+        DataType::FixedSizeList(..) => ValueRef::Array(column.as_fixed_size_list(), row),
+        DataType::Union(..) => ValueRef::Union(column, row),
+        _ => return None,
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use duckdb::Connection;
+    use serde_json::json;
+
+    /// Convert the single value returned by `sql`
+    fn select_json(sql: &str) -> JsonValue {
+        select_json_in(sql, DisplayTimezone::Utc)
+    }
+
+    fn select_json_in(sql: &str, timezone: DisplayTimezone) -> JsonValue {
+        select_with(
+            sql,
+            &ValueFormat {
+                timezone,
+                encoding: ResultEncoding::Plain,
+            },
+        )
+    }
+
+    fn select_typed(sql: &str) -> JsonValue {
+        select_with(
+            sql,
+            &ValueFormat {
+                timezone: DisplayTimezone::Utc,
+                encoding: ResultEncoding::Typed,
+            },
+        )
+    }
+
+    fn select_with(sql: &str, format: &ValueFormat) -> JsonValue {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TYPE mood AS ENUM ('sad', 'ok', 'happy')").unwrap();
+        let mut stmt = conn.prepare(sql).unwrap();
+        let chunk = stmt.query_arrow([]).unwrap().next().unwrap();
+        let column_types = crate::types::column_types(&stmt, Some(&chunk));
+        chunk_row_to_json(&chunk, 0, &column_types, format).remove(0)
+    }
+
+    #[test]
+    fn test_value_ref_to_json() {
+        // Test null
+        let null_val = value_ref_to_json(ValueRef::Null);
+        assert_eq!(null_val, JsonValue::Null);
+
+        // Test int
+        let int_val = value_ref_to_json(ValueRef::Int(42));
+        assert_eq!(int_val, JsonValue::from(42));
+
+        // Test text
+        let text_val = value_ref_to_json(ValueRef::Text(b"hello"));
+        assert_eq!(text_val, JsonValue::from("hello"));
+    }
+
+    #[test]
+    fn test_list() {
+        assert_eq!(select_json("SELECT [1, NULL, 3]"), json!([1, null, 3]));
+        assert_eq!(select_json("SELECT [['a'], []]"), json!([["a"], []]));
+    }
+
+    #[test]
+    fn test_array() {
+        assert_eq!(select_json("SELECT [1.5, 2.5]::DOUBLE[2]"), json!([1.5, 2.5]));
+    }
+
+    #[test]
+    fn test_struct() {
+        assert_eq!(
+            select_json("SELECT {'id': 1, 'tags': ['x'], 'inner': {'ok': true}}"),
+            json!({ "id": 1, "tags": ["x"], "inner": { "ok": true } })
+        );
+    }
+
+    #[test]
+    fn test_map() {
+        assert_eq!(select_json("SELECT MAP {'a': 1, 'b': 2}"), json!({ "a": 1, "b": 2 }));
+        assert_eq!(select_json("SELECT MAP {1: 'one'}"), json!({ "1": "one" }));
+    }
+
+    #[test]
+    fn test_union() {
+        assert_eq!(
+            select_json("SELECT union_value(num := 2)::UNION(num INTEGER, str VARCHAR)"),
+            json!({ "tag": "num", "value": 2 })
+        );
+        assert_eq!(
+            select_json("SELECT union_value(str := 'x')::UNION(num INTEGER, str VARCHAR)"),
+            json!({ "tag": "str", "value": "x" })
+        );
+    }
+
+    #[test]
+    fn test_uuid() {
+        assert_eq!(
+            select_json("SELECT '6a7f1f7e-8c1d-4b5e-9f7a-2f4e1c3b5a6d'::UUID"),
+            json!("6a7f1f7e-8c1d-4b5e-9f7a-2f4e1c3b5a6d")
+        );
+    }
+
+    #[test]
+    fn test_bit() {
+        assert_eq!(select_json("SELECT '101'::BIT"), json!("101"));
+        assert_eq!(select_json("SELECT '0000111100001'::BIT"), json!("0000111100001"));
+    }
+
+    #[test]
+    fn test_enum() {
+        assert_eq!(select_json("SELECT 'happy'::mood"), json!("happy"));
+        assert_eq!(select_json("SELECT ['sad'::mood]"), json!(["sad"]));
+    }
+
+    #[test]
+    fn test_timestamp_units_and_precision() {
+        assert_eq!(
+            select_json("SELECT TIMESTAMP '2024-03-01 12:30:00'"),
+            json!("2024-03-01 12:30:00")
+        );
+        assert_eq!(
+            select_json("SELECT TIMESTAMP '2024-03-01 12:30:00.25'"),
+            json!("2024-03-01 12:30:00.250")
+        );
+        assert_eq!(
+            select_json("SELECT TIMESTAMP_S '2024-03-01 12:30:01'"),
+            json!("2024-03-01 12:30:01")
+        );
+        assert_eq!(
+            select_json("SELECT TIMESTAMP_MS '2024-03-01 12:30:01.5'"),
+            json!("2024-03-01 12:30:01.500")
+        );
+        assert_eq!(
+            select_json("SELECT TIMESTAMP_NS '2024-03-01 12:30:01.123456789'"),
+            json!("2024-03-01 12:30:01.123456789")
+        );
+        assert_eq!(
+            select_json("SELECT 'infinity'::TIMESTAMP"),
+            json!("infinity")
+        );
+    }
+
+    #[test]
+    fn test_pre_epoch_values() {
+        assert_eq!(
+            select_json("SELECT TIMESTAMP_NS '1960-01-01 00:00:00.123456789'"),
+            json!("1960-01-01 00:00:00.123456789")
+        );
+        assert_eq!(
+            select_json("SELECT TIMESTAMP '1969-12-31 23:59:59.5'"),
+            json!("1969-12-31 23:59:59.500")
+        );
+        assert_eq!(select_json("SELECT DATE '1900-02-28'"), json!("1900-02-28"));
+        assert_eq!(select_json("SELECT DATE '0044-03-15'"), json!("0044-03-15"));
+    }
+
+    #[test]
+    fn test_time() {
+        assert_eq!(select_json("SELECT TIME '08:15:00'"), json!("08:15:00"));
+        assert_eq!(
+            select_json("SELECT TIME '23:59:59.999999'"),
+            json!("23:59:59.999999")
+        );
+    }
+
+    #[test]
+    fn test_timestamptz_display_timezone() {
+        let sql = "SELECT TIMESTAMPTZ '2024-01-01 00:00:00.5+00'";
+        assert_eq!(select_json(sql), json!("2024-01-01 00:00:00.500+00:00"));
+        assert_eq!(
+            select_json_in(
+                sql,
+                DisplayTimezone::try_from("+09:00".to_string()).unwrap()
+            ),
+            json!("2024-01-01 09:00:00.500+09:00")
+        );
+        assert_eq!(
+            select_json_in(
+                sql,
+                DisplayTimezone::try_from("-05:30".to_string()).unwrap()
+            ),
+            json!("2023-12-31 18:30:00.500-05:30")
+        );
+
+        // Nested instants follow the display time zone too; naive timestamps do not
+        let tokyo = DisplayTimezone::try_from("+09:00".to_string()).unwrap();
+        assert_eq!(
+            select_json_in("SELECT [TIMESTAMPTZ '2024-01-01 00:00:00+00']", tokyo),
+            json!(["2024-01-01 09:00:00+09:00"])
+        );
+        assert_eq!(
+            select_json_in(
+                "SELECT {'at': TIMESTAMPTZ '2024-01-01 00:00:00+00', 'naive': TIMESTAMP '2024-01-01 00:00:00'}",
+                tokyo
+            ),
+            json!({"at": "2024-01-01 09:00:00+09:00", "naive": "2024-01-01 00:00:00"})
+        );
+        assert_eq!(
+            select_json_in("SELECT MAP {TIMESTAMPTZ '2024-01-01 00:00:00+00': [TIMESTAMPTZ '2024-01-02 00:00:00+00']}", tokyo),
+            json!({"2024-01-01 09:00:00+09:00": ["2024-01-02 09:00:00+09:00"]})
+        );
+    }
+
+    #[test]
+    fn test_interval_iso_8601() {
+        assert_eq!(
+            select_json("SELECT INTERVAL '1 year 2 months 3 days'"),
+            json!("P1Y2M3D")
+        );
+        assert_eq!(
+            select_json("SELECT INTERVAL '4 hours 5 minutes 6.5 seconds'"),
+            json!("PT4H5M6.5S")
+        );
+        assert_eq!(select_json("SELECT INTERVAL '0 seconds'"), json!("PT0S"));
+        assert_eq!(
+            select_json("SELECT INTERVAL '-1 year 2 days -3 hours 0.5 seconds'"),
+            json!("P-1Y2DT-2H-59M-59.5S")
+        );
+    }
+
+    #[test]
+    fn test_plain_numbers() {
+        assert_eq!(
+            select_json("SELECT 170141183460469231731687303715884105727::HUGEINT"),
+            json!("170141183460469231731687303715884105727")
+        );
+        assert_eq!(
+            select_json("SELECT 340282366920938463463374607431768211455::UHUGEINT"),
+            json!("340282366920938463463374607431768211455")
+        );
+        assert_eq!(select_json("SELECT 12.50::DECIMAL(10,2)"), json!("12.50"));
+    }
+
+    #[test]
+    fn test_typed_numbers() {
+        assert_eq!(
+            select_typed("SELECT 7::TINYINT"),
+            json!({ "type": "TINYINT", "value": 7 })
+        );
+        assert_eq!(
+            select_typed("SELECT 1.5::DOUBLE"),
+            json!({ "type": "DOUBLE", "value": 1.5 })
+        );
+        assert_eq!(
+            select_typed("SELECT 9007199254740993::BIGINT"),
+            json!({ "type": "BIGINT", "value": "9007199254740993" })
+        );
+        assert_eq!(
+            select_typed("SELECT 18446744073709551615::UBIGINT"),
+            json!({ "type": "UBIGINT", "value": "18446744073709551615" })
+        );
+        assert_eq!(
+            select_typed("SELECT (-170141183460469231731687303715884105728)::HUGEINT"),
+            json!({ "type": "HUGEINT", "value": "-170141183460469231731687303715884105728" })
+        );
+        assert_eq!(
+            select_typed("SELECT 1::UHUGEINT"),
+            json!({ "type": "UHUGEINT", "value": "1" })
+        );
+        assert_eq!(
+            select_typed("SELECT 123456789012345678.901::DECIMAL(38,3)"),
+            json!({ "type": "DECIMAL", "value": "123456789012345678.901" })
+        );
+        assert_eq!(
+            select_typed("SELECT 'nan'::DOUBLE"),
+            json!({ "type": "DOUBLE", "value": "NaN" })
+        );
+        assert_eq!(select_typed("SELECT 'x'"), json!("x"));
+    }
+
+    #[test]
+    fn test_typed_nested_numbers() {
+        assert_eq!(
+            select_typed("SELECT {'id': 9007199254740993::BIGINT, 'ids': [1::UBIGINT]}"),
+            json!({
+                "id": { "type": "BIGINT", "value": "9007199254740993" },
+                "ids": [{ "type": "UBIGINT", "value": "1" }]
+            })
+        );
+        assert_eq!(
+            select_typed("SELECT MAP {1::BIGINT: 'one'}"),
+            json!({ "1": "one" })
+        );
+    }
+
+    #[test]
+    fn test_arrow_values() {
+        use duckdb::arrow::array::{Int32Array, NullArray, Time32SecondArray};
+        use std::sync::Arc;
+
+        let format = ValueFormat::default();
+        let ints: ArrayRef = Arc::new(Int32Array::from(vec![Some(5), None]));
+        assert_eq!(arrow_value_to_json(&ints, 0, "INTEGER", &format), json!(5));
+        assert_eq!(arrow_value_to_json(&ints, 1, "INTEGER", &format), JsonValue::Null);
+
+        let nulls: ArrayRef = Arc::new(NullArray::new(1));
+        assert_eq!(arrow_value_to_json(&nulls, 0, "NULL", &format), JsonValue::Null);
+
+        // Types with no duckdb-rs value are rendered by Arrow rather than failing
+        let times: ArrayRef = Arc::new(Time32SecondArray::from(vec![3_723]));
+        assert_eq!(arrow_value_to_json(&times, 0, "TIME", &format), json!("01:02:03"));
+    }
+}
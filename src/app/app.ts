@@ -9,6 +9,7 @@ import { DataTableComponent } from './components/data-table/data-table.component
 import { SchemaViewerComponent } from './components/schema-viewer/schema-viewer.component';
 import { DashboardComponent } from './components/dashboard/dashboard.component';
 import { TableInfo } from './models/database.model';
+import { qualifiedTableName } from './utils/sql';
 
 @Component({
   selector: 'app-root',
@@ -170,7 +171,7 @@ export class App {
   async onTableSelect(table: TableInfo): Promise<void> {
     // Show both schema and data when selecting a table
     this.showSchema.set(true);
-    const query = `SELECT * FROM "${table.name}" LIMIT 100`;
+    const query = `SELECT * FROM ${qualifiedTableName(table)} LIMIT 100`;
     this.currentQuery.set(query);
 
     const editor = this.sqlEditor();
@@ -8,9 +8,11 @@ import { MatInputModule } from '@angular/material/input';
 import { MatSelectModule } from '@angular/material/select';
 import { MatIconModule } from '@angular/material/icon';
 import { MatProgressSpinnerModule } from '@angular/material/progress-spinner';
-import { DashboardWidget, WidgetType, WidgetConfig } from '../../models/database.model';
+import { DashboardWidget, TableInfo, WidgetType, WidgetConfig } from '../../models/database.model';
 import { QueryService } from '../../services/query.service';
 import { DatabaseService } from '../../services/database.service';
+import { qualifiedTableName } from '../../utils/sql';
+import { errorMessage } from '../../utils/error';
 
 export interface WidgetDialogData {
   type: WidgetType;
@@ -53,8 +55,8 @@ export interface WidgetDialogResult {
           <mat-label>Select Table (optional)</mat-label>
           <mat-select [(ngModel)]="selectedTable" (selectionChange)="onTableSelect()">
             <mat-option [value]="null">-- Custom Query --</mat-option>
-            @for (table of db.tables(); track table.name) {
-              <mat-option [value]="table.name">{{ table.name }}</mat-option>
+            @for (table of db.tables(); track qualifiedName(table)) {
+              <mat-option [value]="qualifiedName(table)">{{ table.name }}</mat-option>
             }
           </mat-select>
         </mat-form-field>
@@ -220,9 +222,13 @@ export class WidgetDialogComponent {
     }
   }
 
+  qualifiedName(table: TableInfo): string {
+    return qualifiedTableName(table);
+  }
+
   onTableSelect(): void {
     if (this.selectedTable) {
-      this.query = `SELECT * FROM "${this.selectedTable}" LIMIT 100`;
+      this.query = `SELECT * FROM ${this.selectedTable} LIMIT 100`;
       this.executeQuery();
     }
   }
@@ -247,7 +253,7 @@ export class WidgetDialogComponent {
         this.config.valueColumn = result.columns[0];
       }
     } catch (e) {
-      this.queryError.set(e instanceof Error ? e.message : String(e));
+      this.queryError.set(errorMessage(e));
       this.columns.set([]);
     } finally {
       this.isExecuting.set(false);
@@ -1,4 +1,4 @@
-import { Component, inject } from '@angular/core';
+import { Component, computed, inject } from '@angular/core';
 import { CommonModule } from '@angular/common';
 import { MatTableModule } from '@angular/material/table';
 import { MatIconModule } from '@angular/material/icon';
@@ -21,6 +21,9 @@ import { DatabaseService } from '../../services/database.service';
           <mat-icon>table_chart</mat-icon>
           <h3>{{ db.selectedTableSchema()!.table_name }}</h3>
         </div>
+        @if (db.selectedTableSchema()!.comment) {
+          <p class="comment">{{ db.selectedTableSchema()!.comment }}</p>
+        }
 
         <table mat-table [dataSource]="db.selectedTableSchema()!.columns" class="schema-table">
           <ng-container matColumnDef="name">
@@ -30,7 +33,7 @@ import { DatabaseService } from '../../services/database.service';
                 @if (column.is_primary_key) {
                   <mat-icon class="pk-icon">key</mat-icon>
                 }
-                {{ column.name }}
+                <span [title]="column.comment ?? ''">{{ column.name }}</span>
               </div>
             </td>
           </ng-container>
@@ -56,13 +59,30 @@ import { DatabaseService } from '../../services/database.service';
           <ng-container matColumnDef="default">
             <th mat-header-cell *matHeaderCellDef>Default</th>
             <td mat-cell *matCellDef="let column">
-              {{ column.default_value || '-' }}
+              @if (column.generated_expression) {
+                <span class="generated">= {{ column.generated_expression }}</span>
+              } @else {
+                {{ column.default_value || '-' }}
+              }
             </td>
           </ng-container>
 
           <tr mat-header-row *matHeaderRowDef="displayedColumns"></tr>
           <tr mat-row *matRowDef="let row; columns: displayedColumns"></tr>
         </table>
+
+        @if (constraints().length > 0) {
+          <h4>Constraints &amp; Indexes</h4>
+          <ul class="constraints">
+            @for (constraint of constraints(); track constraint.name) {
+              <li>
+                <span class="constraint-kind">{{ constraint.kind }}</span>
+                <span class="constraint-name">{{ constraint.name }}</span>
+                {{ constraint.definition }}
+              </li>
+            }
+          </ul>
+        }
       </div>
     }
   `,
@@ -131,10 +151,71 @@ import { DatabaseService } from '../../services/database.service';
     .nullable-no {
       color: #e57373;
     }
+
+    .comment {
+      margin: -8px 0 16px;
+      color: #9e9e9e;
+    }
+
+    .generated {
+      font-style: italic;
+    }
+
+    h4 {
+      margin: 16px 0 8px;
+      color: #d4d4d4;
+      font-weight: 500;
+    }
+
+    .constraints {
+      margin: 0;
+      padding-left: 16px;
+      color: #d4d4d4;
+      font-family: monospace;
+      font-size: 12px;
+    }
+
+    .constraint-kind {
+      color: #4fc3f7;
+      margin-right: 8px;
+    }
+
+    .constraint-name {
+      color: #9e9e9e;
+      margin-right: 8px;
+    }
   `],
 })
 export class SchemaViewerComponent {
   db = inject(DatabaseService);
 
   displayedColumns = ['name', 'type', 'nullable', 'default'];
+
+  /** Unique, check and foreign key constraints and indexes of the selected table, one line each */
+  constraints = computed(() => {
+    const schema = this.db.selectedTableSchema();
+    if (!schema) return [];
+    return [
+      ...schema.unique_constraints.map((c) => ({
+        kind: 'UNIQUE',
+        name: c.name,
+        definition: `(${c.columns.join(', ')})`,
+      })),
+      ...schema.check_constraints.map((c) => ({
+        kind: 'CHECK',
+        name: c.name,
+        definition: c.expression ?? '',
+      })),
+      ...schema.foreign_keys.map((fk) => ({
+        kind: 'FOREIGN KEY',
+        name: fk.name,
+        definition: `(${fk.columns.join(', ')}) → ${fk.referenced_table}(${fk.referenced_columns.join(', ')})`,
+      })),
+      ...schema.indexes.map((index) => ({
+        kind: index.is_unique ? 'UNIQUE INDEX' : 'INDEX',
+        name: index.name,
+        definition: index.expressions ?? '',
+      })),
+    ];
+  });
 }
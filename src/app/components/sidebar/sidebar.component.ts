@@ -7,6 +7,7 @@ import { MatTooltipModule } from '@angular/material/tooltip';
 import { MatProgressSpinnerModule } from '@angular/material/progress-spinner';
 import { DatabaseService } from '../../services/database.service';
 import { TableInfo } from '../../models/database.model';
+import { qualifiedTableName } from '../../utils/sql';
 
 @Component({
   selector: 'app-sidebar',
@@ -24,6 +25,9 @@ import { TableInfo } from '../../models/database.model';
       <div class="sidebar-header">
         <h3>Tables</h3>
         @if (db.isConnected()) {
+          <button mat-icon-button (click)="onCountRows()" matTooltip="Count rows exactly">
+            <mat-icon>tag</mat-icon>
+          </button>
           <button mat-icon-button (click)="onRefresh()" matTooltip="Refresh">
             <mat-icon>refresh</mat-icon>
           </button>
@@ -49,16 +53,20 @@ import { TableInfo } from '../../models/database.model';
         </div>
       } @else {
         <mat-nav-list>
-          @for (table of db.tables(); track table.name) {
+          @for (table of db.tables(); track qualifiedName(table)) {
             <a
               mat-list-item
-              [class.selected]="db.selectedTable() === table.name"
+              [class.selected]="db.selectedTable() === qualifiedName(table)"
               (click)="onSelectTable(table)"
             >
-              <mat-icon matListItemIcon>table_chart</mat-icon>
-              <span matListItemTitle>{{ table.name }}</span>
-              <span matListItemMeta class="row-count">
-                {{ formatRowCount(table.row_count) }}
+              <mat-icon matListItemIcon>{{ tableIcon(table) }}</mat-icon>
+              <span matListItemTitle>{{ tableLabel(table) }}</span>
+              <span
+                matListItemMeta
+                class="row-count"
+                [title]="table.row_count_error ?? (table.row_count_exact ? '' : 'Estimated row count')"
+              >
+                {{ formatRowCount(table) }}
               </span>
             </a>
           }
@@ -146,12 +154,51 @@ export class SidebarComponent {
     await this.db.refreshTables();
   }
 
+  async onCountRows(): Promise<void> {
+    await this.db.refreshTables(true);
+  }
+
   async onSelectTable(table: TableInfo): Promise<void> {
-    await this.db.selectTable(table.name);
+    await this.db.selectTable(table);
     this.tableSelect.emit(table);
   }
 
-  formatRowCount(count: number): string {
+  qualifiedName(table: TableInfo): string {
+    return qualifiedTableName(table);
+  }
+
+  /** Table name, prefixed with its database and schema unless it is in the default ones */
+  tableLabel(table: TableInfo): string {
+    const defaultDatabase = this.db.databases().find((d) => d.is_default)?.name;
+    if (table.database !== defaultDatabase) {
+      return `${table.database}.${table.schema}.${table.name}`;
+    }
+    return table.schema === 'main' ? table.name : `${table.schema}.${table.name}`;
+  }
+
+  tableIcon(table: TableInfo): string {
+    switch (table.table_type) {
+      case 'view':
+        return 'visibility';
+      case 'file_view':
+        return 'description';
+      default:
+        return 'table_chart';
+    }
+  }
+
+  formatRowCount(table: TableInfo): string {
+    const count = table.row_count;
+    if (count === null) {
+      return table.row_count_error ? '?' : '';
+    }
+    if (!table.row_count_exact) {
+      return `~${this.formatCount(count)}`;
+    }
+    return this.formatCount(count);
+  }
+
+  private formatCount(count: number): string {
     if (count >= 1000000) {
       return `${(count / 1000000).toFixed(1)}M`;
     }
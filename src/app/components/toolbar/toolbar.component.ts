@@ -38,6 +38,10 @@ import { TauriService } from '../../services/tauri.service';
           <mat-icon>add</mat-icon>
           <span>New Database...</span>
         </button>
+        <button mat-menu-item (click)="onOpenMemoryDatabase()">
+          <mat-icon>memory</mat-icon>
+          <span>New In-Memory Database</span>
+        </button>
         @if (db.recentDatabases().length > 0) {
           <mat-divider></mat-divider>
           <button mat-menu-item [matMenuTriggerFor]="recentMenu">
@@ -47,6 +51,12 @@ import { TauriService } from '../../services/tauri.service';
         }
         @if (db.isConnected()) {
           <mat-divider></mat-divider>
+          @if (db.isInMemory()) {
+            <button mat-menu-item (click)="onPersistDatabase()">
+              <mat-icon>save</mat-icon>
+              <span>Save to File...</span>
+            </button>
+          }
           <button mat-menu-item (click)="onCloseDatabase()">
             <mat-icon>close</mat-icon>
             <span>Close Database</span>
@@ -154,6 +164,14 @@ export class ToolbarComponent {
     await this.db.createDatabase();
   }
 
+  async onOpenMemoryDatabase(): Promise<void> {
+    await this.db.openMemoryDatabase();
+  }
+
+  async onPersistDatabase(): Promise<void> {
+    await this.db.persistDatabase();
+  }
+
   async onCloseDatabase(): Promise<void> {
     await this.db.closeDatabase();
   }
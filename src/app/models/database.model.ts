@@ -1,11 +1,94 @@
+/**
+ * A file is open read-write in one process or read-only in any number of
+ * processes, never both: 'read_only' shares it with other readers only
+ */
+export type AccessMode = 'read_only' | 'read_write';
+
 export interface DatabaseInfo {
   path: string;
   tables: string[];
+  access_mode: AccessMode;
+}
+
+export interface CurrentDatabase {
+  path: string;
+  access_mode: AccessMode;
+  /** A scratch database opened with openMemoryDatabase */
+  in_memory: boolean;
 }
 
+/** Path reported for an in-memory database */
+export const IN_MEMORY_PATH = ':memory:';
+
+/** A database of the session: the opened file or one attached to it */
+export interface AttachedDatabase {
+  name: string;
+  path: string | null;
+  /** DuckDB storage type: 'duckdb', 'sqlite', 'postgres', ... */
+  source_type: string;
+  access_mode: AccessMode;
+  is_default: boolean;
+}
+
+/** SQLite and DuckDB sources are files; Postgres and MySQL sources are connection strings */
+export type SourceType = 'duckdb' | 'sqlite' | 'postgres' | 'mysql';
+
+/** 'file_view' reads CSV/Parquet/JSON files in place (see registerFileView) */
+export type TableType = 'table' | 'view' | 'file_view';
+
 export interface TableInfo {
   name: string;
-  row_count: number;
+  database: string;
+  schema: string;
+  table_type: TableType;
+  /** DuckDB's estimate unless row_count_exact; null for views and failed counts */
+  row_count: number | null;
+  row_count_exact: boolean;
+  row_count_error: string | null;
+  /** Bytes the table occupies in the database file; null when not asked for or not stored there (yet) */
+  size_bytes: number | null;
+}
+
+export type FileViewFormat = 'csv' | 'parquet' | 'json';
+
+export type CatalogObjectType =
+  | 'table'
+  | 'view'
+  | 'file_view'
+  | 'sequence'
+  | 'macro'
+  | 'table_macro'
+  | 'index';
+
+export interface CatalogObject {
+  name: string;
+  object_type: CatalogObjectType;
+  comment: string | null;
+  temporary: boolean;
+  /** The indexed table, for indexes */
+  table_name: string | null;
+}
+
+/** An object to generate DDL for; database and schema default to the session's */
+export interface ObjectRef {
+  database?: string;
+  schema?: string;
+  name: string;
+  object_type: CatalogObjectType;
+}
+
+export interface CatalogSchema {
+  name: string;
+  comment: string | null;
+  objects: CatalogObject[];
+}
+
+/** A database of the catalog tree; 'temp' holds the session's temporary objects */
+export interface CatalogDatabase {
+  name: string;
+  path: string | null;
+  is_default: boolean;
+  schemas: CatalogSchema[];
 }
 
 export interface ColumnInfo {
@@ -14,11 +97,92 @@ export interface ColumnInfo {
   nullable: boolean;
   default_value: string | null;
   is_primary_key: boolean;
+  comment: string | null;
+  /** Expression of a GENERATED ALWAYS AS column, when DuckDB reports the column as generated */
+  generated_expression: string | null;
+}
+
+export interface UniqueConstraint {
+  name: string;
+  columns: string[];
+}
+
+export interface CheckConstraint {
+  name: string;
+  expression: string | null;
+}
+
+/** DuckDB foreign keys reference a table in the same schema */
+export interface ForeignKey {
+  name: string;
+  columns: string[];
+  referenced_table: string | null;
+  referenced_columns: string[];
+}
+
+export interface IndexInfo {
+  name: string;
+  is_unique: boolean;
+  /** Indexed columns and expressions as DuckDB lists them */
+  expressions: string | null;
+  sql: string | null;
 }
 
 export interface TableSchema {
   table_name: string;
+  database: string;
+  schema: string;
+  comment: string | null;
   columns: ColumnInfo[];
+  unique_constraints: UniqueConstraint[];
+  check_constraints: CheckConstraint[];
+  foreign_keys: ForeignKey[];
+  indexes: IndexInfo[];
+}
+
+/** Profile only `columns`, and a random sample of `sample` rows */
+export interface ProfileOptions {
+  columns?: string[];
+  sample?: number;
+}
+
+export interface QuantileValue {
+  quantile: number;
+  value: number;
+}
+
+export interface ValueCount {
+  value: string | null;
+  count: number;
+}
+
+/** Values in [lower, upper); the last bin includes upper */
+export interface HistogramBin {
+  lower: number;
+  upper: number;
+  count: number;
+}
+
+/** Mean, stddev, quantiles and histogram are only computed for numeric columns */
+export interface ColumnProfile {
+  name: string;
+  data_type: string;
+  null_count: number;
+  null_percent: number;
+  distinct_count: number;
+  min: string | null;
+  max: string | null;
+  mean: number | null;
+  stddev: number | null;
+  quantiles: QuantileValue[];
+  top_values: ValueCount[];
+  histogram: HistogramBin[];
+}
+
+export interface TableProfile {
+  row_count: number;
+  sampled: boolean;
+  columns: ColumnProfile[];
 }
 
 export interface QueryResult {
@@ -29,6 +193,167 @@ export interface QueryResult {
   execution_time_ms: number;
 }
 
+export interface StatementResult {
+  index: number;
+  sql: string;
+  result: QueryResult | null;
+  rows_affected: number | null;
+  execution_time_ms: number;
+}
+
+export interface ScriptError {
+  statement_index: number;
+  line: number;
+  column: number;
+  message: string;
+}
+
+export interface ScriptResult {
+  statements: StatementResult[];
+  error: ScriptError | null;
+  execution_time_ms: number;
+}
+
+export interface CursorInfo {
+  cursor_id: string;
+  columns: string[];
+  column_types: string[];
+}
+
+export interface RowBatch {
+  rows: unknown[][];
+  row_count: number;
+  done: boolean;
+}
+
+export interface TimeoutSettings {
+  query_ms: number;
+  import_ms: number | null;
+  export_ms: number | null;
+  metadata_ms: number;
+}
+
+/** 'local', 'UTC' or a fixed offset such as '+09:00' */
+export type DisplayTimezone = string;
+
+export interface DisplaySettings {
+  timezone: DisplayTimezone;
+}
+
+export interface Settings {
+  timeouts: TimeoutSettings;
+  display: DisplaySettings;
+}
+
+export interface BlobData {
+  size: number;
+  mime_type: string;
+  base64: string;
+  hex_preview: string;
+}
+
+/** 'typed' returns numbers as { type, value } with exact strings for wide integers and decimals */
+export type ResultEncoding = 'plain' | 'typed';
+
+export interface TypedNumber {
+  type: string;
+  value: number | string;
+}
+
+export type AppErrorKind =
+  | 'noDatabase'
+  | 'timeout'
+  | 'cancelled'
+  | 'sqlSyntax'
+  | 'binder'
+  | 'catalog'
+  | 'conversion'
+  | 'readOnly'
+  | 'constraint'
+  | 'io'
+  | 'extension'
+  | 'invalidInput'
+  | 'database'
+  | 'internal';
+
+/** Error rejected by every command; `line`/`column` (1-based) locate the error in the SQL when known */
+export interface AppError {
+  kind: AppErrorKind;
+  message: string;
+  line: number | null;
+  column: number | null;
+}
+
+export type ParamType =
+  | 'null'
+  | 'bool'
+  | 'int'
+  | 'double'
+  | 'decimal'
+  | 'text'
+  | 'date'
+  | 'timestamp'
+  | 'list';
+
+/** A typed bind parameter; decimals are strings, list values arrays of parameters */
+export interface QueryParam {
+  type: ParamType;
+  value?: boolean | number | string | QueryParam[] | null;
+}
+
+/** Values for `?`/`$1` placeholders in order, or for `$name` placeholders by name */
+export type QueryParams = QueryParam[] | Record<string, QueryParam>;
+
+export type FilterOp =
+  | 'eq'
+  | 'ne'
+  | 'lt'
+  | 'le'
+  | 'gt'
+  | 'ge'
+  | 'like'
+  | 'not_like'
+  | 'ilike'
+  | 'not_ilike'
+  | 'in'
+  | 'not_in'
+  | 'between'
+  | 'not_between'
+  | 'is_null'
+  | 'is_not_null';
+
+/** Comparisons and LIKE take `value`, IN and BETWEEN `values`, NULL checks neither */
+export interface FilterCondition {
+  column: string;
+  op: FilterOp;
+  value?: QueryParam;
+  values?: QueryParam[];
+}
+
+export type Filter =
+  | { and: Filter[] }
+  | { or: Filter[] }
+  | { condition: FilterCondition };
+
+export interface SortSpec {
+  column: string;
+  descending?: boolean;
+  /** NULLs sort last unless set */
+  nulls_first?: boolean;
+}
+
+export interface TableDataOptions {
+  sort?: SortSpec[];
+  filter?: Filter;
+}
+
+/** A page of table data with the number of rows matching the filter */
+export interface TablePage extends QueryResult {
+  total_rows: number;
+  /** rowid of each row, for getCellBlob and saveBlob; null for views */
+  rowids: number[] | null;
+}
+
 export type ExportFormat = 'csv' | 'excel' | 'parquet' | 'json';
 
 export interface ExportOptions {
@@ -1,6 +1,7 @@
 import { Injectable, signal } from '@angular/core';
 import { TauriService } from './tauri.service';
 import { Dashboard, DashboardWidget, WidgetType, WidgetConfig } from '../models/database.model';
+import { errorMessage } from '../utils/error';
 
 @Injectable({
   providedIn: 'root',
@@ -26,7 +27,7 @@ export class DashboardService {
       const dashboards = await this.tauri.loadDashboards();
       this._dashboards.set(dashboards);
     } catch (e) {
-      this._error.set(e instanceof Error ? e.message : String(e));
+      this._error.set(errorMessage(e));
     } finally {
       this._isLoading.set(false);
     }
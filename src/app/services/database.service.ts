@@ -1,10 +1,18 @@
 import { Injectable, signal, computed } from '@angular/core';
 import { TauriService } from './tauri.service';
 import {
+  AccessMode,
+  AttachedDatabase,
+  CatalogDatabase,
   DatabaseInfo,
+  FileViewFormat,
+  IN_MEMORY_PATH,
+  SourceType,
   TableInfo,
   TableSchema,
 } from '../models/database.model';
+import { errorMessage } from '../utils/error';
+import { qualifiedTableName } from '../utils/sql';
 
 @Injectable({
   providedIn: 'root',
@@ -12,7 +20,10 @@ import {
 export class DatabaseService {
   private _isConnected = signal(false);
   private _currentDatabase = signal<string | null>(null);
+  private _accessMode = signal<AccessMode | null>(null);
+  private _databases = signal<AttachedDatabase[]>([]);
   private _tables = signal<TableInfo[]>([]);
+  private _catalog = signal<CatalogDatabase[]>([]);
   private _selectedTable = signal<string | null>(null);
   private _selectedTableSchema = signal<TableSchema | null>(null);
   private _recentDatabases = signal<string[]>([]);
@@ -21,7 +32,12 @@ export class DatabaseService {
 
   readonly isConnected = this._isConnected.asReadonly();
   readonly currentDatabase = this._currentDatabase.asReadonly();
+  readonly accessMode = this._accessMode.asReadonly();
+  readonly isReadOnly = computed(() => this._accessMode() === 'read_only');
+  readonly isInMemory = computed(() => this._currentDatabase() === IN_MEMORY_PATH);
+  readonly databases = this._databases.asReadonly();
   readonly tables = this._tables.asReadonly();
+  readonly catalog = this._catalog.asReadonly();
   readonly selectedTable = this._selectedTable.asReadonly();
   readonly selectedTableSchema = this._selectedTableSchema.asReadonly();
   readonly recentDatabases = this._recentDatabases.asReadonly();
@@ -31,6 +47,7 @@ export class DatabaseService {
   readonly databaseName = computed(() => {
     const path = this._currentDatabase();
     if (!path) return null;
+    if (path === IN_MEMORY_PATH) return 'In-memory';
     const parts = path.split(/[/\\]/);
     return parts[parts.length - 1];
   });
@@ -56,7 +73,7 @@ export class DatabaseService {
     await this.tauri.saveRecentDatabases(updated);
   }
 
-  async openDatabase(path?: string): Promise<void> {
+  async openDatabase(path?: string, accessMode?: AccessMode): Promise<void> {
     this._isLoading.set(true);
     this._error.set(null);
 
@@ -71,13 +88,14 @@ export class DatabaseService {
         dbPath = selected;
       }
 
-      const info = await this.tauri.openDatabase(dbPath);
+      const info = await this.tauri.openDatabase(dbPath, accessMode);
       this._currentDatabase.set(info.path);
+      this._accessMode.set(info.access_mode);
       this._isConnected.set(true);
       await this.addToRecentDatabases(info.path);
       await this.refreshTables();
     } catch (e) {
-      this._error.set(e instanceof Error ? e.message : String(e));
+      this._error.set(errorMessage(e));
       throw e;
     } finally {
       this._isLoading.set(false);
@@ -97,50 +115,174 @@ export class DatabaseService {
 
       const info = await this.tauri.createDatabase(path);
       this._currentDatabase.set(info.path);
+      this._accessMode.set(info.access_mode);
       this._isConnected.set(true);
       await this.addToRecentDatabases(info.path);
       this._tables.set([]);
+      this._catalog.set([]);
     } catch (e) {
-      this._error.set(e instanceof Error ? e.message : String(e));
+      this._error.set(errorMessage(e));
       throw e;
     } finally {
       this._isLoading.set(false);
     }
   }
 
+  /** Start a scratch database in memory; it is not added to the recent databases */
+  async openMemoryDatabase(): Promise<void> {
+    this._isLoading.set(true);
+    this._error.set(null);
+
+    try {
+      const info = await this.tauri.openMemoryDatabase();
+      this._currentDatabase.set(info.path);
+      this._accessMode.set(info.access_mode);
+      this._isConnected.set(true);
+      await this.refreshTables();
+    } catch (e) {
+      this._error.set(errorMessage(e));
+      throw e;
+    } finally {
+      this._isLoading.set(false);
+    }
+  }
+
+  /** Save the in-memory database to a new file chosen by the user */
+  async persistDatabase(): Promise<void> {
+    this._error.set(null);
+
+    try {
+      const path = await this.tauri.saveFileDialog();
+      if (!path) return;
+
+      await this.tauri.persistDatabase(path);
+      await this.addToRecentDatabases(path);
+    } catch (e) {
+      this._error.set(errorMessage(e));
+      throw e;
+    }
+  }
+
   async closeDatabase(): Promise<void> {
     try {
       await this.tauri.closeDatabase();
       this._currentDatabase.set(null);
+      this._accessMode.set(null);
       this._isConnected.set(false);
+      this._databases.set([]);
       this._tables.set([]);
+      this._catalog.set([]);
       this._selectedTable.set(null);
       this._selectedTableSchema.set(null);
     } catch (e) {
-      this._error.set(e instanceof Error ? e.message : String(e));
+      this._error.set(errorMessage(e));
       throw e;
     }
   }
 
-  async refreshTables(): Promise<void> {
+  /** Reload the table list; `exactCounts` counts every table instead of using estimates */
+  async refreshTables(exactCounts?: boolean): Promise<void> {
     if (!this._isConnected()) return;
 
     try {
-      const tables = await this.tauri.getTables();
+      const [databases, tables, catalog] = await Promise.all([
+        this.tauri.listDatabases(),
+        this.tauri.getTables(exactCounts),
+        this.tauri.getCatalog(),
+      ]);
+      this._databases.set(databases);
       this._tables.set(tables);
+      this._catalog.set(catalog);
+    } catch (e) {
+      this._error.set(errorMessage(e));
+      throw e;
+    }
+  }
+
+  async attachDatabase(path?: string, alias?: string, accessMode?: AccessMode): Promise<void> {
+    this._error.set(null);
+
+    try {
+      let dbPath = path;
+      if (!dbPath) {
+        const selected = await this.tauri.openFileDialog();
+        if (!selected) return;
+        dbPath = selected;
+      }
+
+      await this.tauri.attachDatabase(dbPath, alias, accessMode);
+      await this.refreshTables();
+    } catch (e) {
+      this._error.set(errorMessage(e));
+      throw e;
+    }
+  }
+
+  /** Attach a SQLite file or database server so its tables list alongside native ones */
+  async attachSource(
+    sourceType: SourceType,
+    location: string,
+    alias?: string,
+    accessMode?: AccessMode
+  ): Promise<void> {
+    this._error.set(null);
+
+    try {
+      await this.tauri.attachSource(sourceType, location, alias, accessMode);
+      await this.refreshTables();
+    } catch (e) {
+      this._error.set(errorMessage(e));
+      throw e;
+    }
+  }
+
+  /** Browse CSV/Parquet/JSON files in place through a view instead of importing them */
+  async registerFileView(
+    name: string,
+    paths: string[],
+    format: FileViewFormat,
+    temporary?: boolean
+  ): Promise<void> {
+    this._error.set(null);
+
+    try {
+      await this.tauri.registerFileView(name, paths, format, temporary);
+      await this.refreshTables();
+    } catch (e) {
+      this._error.set(errorMessage(e));
+      throw e;
+    }
+  }
+
+  async detachDatabase(name: string): Promise<void> {
+    try {
+      await this.tauri.detachDatabase(name);
+      await this.refreshTables();
+    } catch (e) {
+      this._error.set(errorMessage(e));
+      throw e;
+    }
+  }
+
+  /** Make `name` the database unqualified table names resolve against */
+  async useDatabase(name: string, schema?: string): Promise<void> {
+    try {
+      await this.tauri.useDatabase(name, schema);
+      await this.refreshTables();
     } catch (e) {
-      this._error.set(e instanceof Error ? e.message : String(e));
+      this._error.set(errorMessage(e));
       throw e;
     }
   }
 
-  async selectTable(tableName: string): Promise<void> {
-    this._selectedTable.set(tableName);
+  /** Select `table`; `selectedTable` holds its qualified name */
+  async selectTable(table: TableInfo): Promise<void> {
+    this._selectedTable.set(qualifiedTableName(table));
     try {
-      const schema = await this.tauri.getTableSchema(tableName);
+      const schema = await this.tauri.getTableSchema(table.name, table.database, table.schema);
       this._selectedTableSchema.set(schema);
     } catch (e) {
-      this._error.set(e instanceof Error ? e.message : String(e));
+      this._error.set(errorMessage(e));
       throw e;
     }
   }
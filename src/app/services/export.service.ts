@@ -1,6 +1,7 @@
 import { Injectable, signal } from '@angular/core';
 import { TauriService } from './tauri.service';
 import { ExportFormat } from '../models/database.model';
+import { errorMessage } from '../utils/error';
 
 @Injectable({
   providedIn: 'root',
@@ -37,8 +38,8 @@ export class ExportService {
 
       await this.tauri.exportData(format, filePath, query);
     } catch (e) {
-      const errorMessage = e instanceof Error ? e.message : String(e);
-      this._exportError.set(errorMessage);
+      const message = errorMessage(e);
+      this._exportError.set(message);
       throw e;
     } finally {
       this._isExporting.set(false);
@@ -47,7 +48,9 @@ export class ExportService {
 
   async importFile(
     format: 'csv' | 'parquet' | 'excel',
-    tableName: string
+    tableName: string,
+    database?: string,
+    schema?: string
   ): Promise<void> {
     this._isExporting.set(true);
     this._exportError.set(null);
@@ -62,18 +65,18 @@ export class ExportService {
 
       switch (format) {
         case 'csv':
-          await this.tauri.importCsv(filePath, tableName);
+          await this.tauri.importCsv(filePath, tableName, database, schema);
           break;
         case 'parquet':
-          await this.tauri.importParquet(filePath, tableName);
+          await this.tauri.importParquet(filePath, tableName, database, schema);
           break;
         case 'excel':
-          await this.tauri.importExcel(filePath, tableName);
+          await this.tauri.importExcel(filePath, tableName, undefined, database, schema);
           break;
       }
     } catch (e) {
-      const errorMessage = e instanceof Error ? e.message : String(e);
-      this._exportError.set(errorMessage);
+      const message = errorMessage(e);
+      this._exportError.set(message);
       throw e;
     } finally {
       this._isExporting.set(false);
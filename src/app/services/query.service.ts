@@ -1,6 +1,7 @@
 import { Injectable, signal } from '@angular/core';
 import { TauriService } from './tauri.service';
-import { QueryResult } from '../models/database.model';
+import { AppError, QueryResult, TableDataOptions, TablePage } from '../models/database.model';
+import { errorMessage, isAppError } from '../utils/error';
 
 export interface QueryHistoryItem {
   id: string;
@@ -19,11 +20,14 @@ export class QueryService {
   private _isExecuting = signal(false);
   private _currentResult = signal<QueryResult | null>(null);
   private _currentError = signal<string | null>(null);
+  private _currentErrorDetail = signal<AppError | null>(null);
   private _queryHistory = signal<QueryHistoryItem[]>([]);
 
   readonly isExecuting = this._isExecuting.asReadonly();
   readonly currentResult = this._currentResult.asReadonly();
   readonly currentError = this._currentError.asReadonly();
+  /** Structured backend error, with the SQL location when DuckDB reports one */
+  readonly currentErrorDetail = this._currentErrorDetail.asReadonly();
   readonly queryHistory = this._queryHistory.asReadonly();
 
   constructor(private tauri: TauriService) {}
@@ -31,6 +35,7 @@ export class QueryService {
   async executeQuery(sql: string): Promise<QueryResult> {
     this._isExecuting.set(true);
     this._currentError.set(null);
+    this._currentErrorDetail.set(null);
 
     const historyItem: QueryHistoryItem = {
       id: crypto.randomUUID(),
@@ -52,9 +57,10 @@ export class QueryService {
       this.addToHistory(historyItem);
       return result;
     } catch (e) {
-      const errorMessage = e instanceof Error ? e.message : String(e);
-      this._currentError.set(errorMessage);
-      historyItem.error = errorMessage;
+      const message = errorMessage(e);
+      this._currentError.set(message);
+      this._currentErrorDetail.set(isAppError(e) ? e : null);
+      historyItem.error = message;
       this.addToHistory(historyItem);
       throw e;
     } finally {
@@ -65,18 +71,23 @@ export class QueryService {
   async getTableData(
     tableName: string,
     limit: number = 100,
-    offset: number = 0
-  ): Promise<QueryResult> {
+    offset: number = 0,
+    options?: TableDataOptions,
+    database?: string,
+    schema?: string
+  ): Promise<TablePage> {
     this._isExecuting.set(true);
     this._currentError.set(null);
+    this._currentErrorDetail.set(null);
 
     try {
-      const result = await this.tauri.getTableData(tableName, limit, offset);
+      const result = await this.tauri.getTableData(tableName, limit, offset, options, database, schema);
       this._currentResult.set(result);
       return result;
     } catch (e) {
-      const errorMessage = e instanceof Error ? e.message : String(e);
-      this._currentError.set(errorMessage);
+      const message = errorMessage(e);
+      this._currentError.set(message);
+      this._currentErrorDetail.set(isAppError(e) ? e : null);
       throw e;
     } finally {
       this._isExecuting.set(false);
@@ -96,5 +107,6 @@ export class QueryService {
   clearResult(): void {
     this._currentResult.set(null);
     this._currentError.set(null);
+    this._currentErrorDetail.set(null);
   }
 }
@@ -3,12 +3,30 @@ import { invoke } from '@tauri-apps/api/core';
 import { open, save } from '@tauri-apps/plugin-dialog';
 import { Store } from '@tauri-apps/plugin-store';
 import {
+  AccessMode,
+  AttachedDatabase,
+  CatalogDatabase,
+  CurrentDatabase,
   DatabaseInfo,
+  FileViewFormat,
+  ObjectRef,
+  TableDataOptions,
   TableInfo,
+  TablePage,
+  TableProfile,
   TableSchema,
+  ProfileOptions,
   QueryResult,
+  CursorInfo,
+  RowBatch,
+  ScriptResult,
+  SourceType,
+  BlobData,
   ExportFormat,
+  ResultEncoding,
+  QueryParams,
   Dashboard,
+  Settings,
 } from '../models/database.model';
 
 @Injectable({
@@ -25,8 +43,8 @@ export class TauriService {
   }
 
   // Database operations
-  async openDatabase(path: string): Promise<DatabaseInfo> {
-    return invoke<DatabaseInfo>('open_database', { path });
+  async openDatabase(path: string, accessMode?: AccessMode): Promise<DatabaseInfo> {
+    return invoke<DatabaseInfo>('open_database', { path, accessMode });
   }
 
   async createDatabase(path: string): Promise<DatabaseInfo> {
@@ -37,36 +55,180 @@ export class TauriService {
     return invoke('close_database');
   }
 
-  async getCurrentDatabase(): Promise<string | null> {
-    return invoke<string | null>('get_current_database');
+  async openMemoryDatabase(): Promise<DatabaseInfo> {
+    return invoke<DatabaseInfo>('open_memory_database');
+  }
+
+  /** Write the in-memory database to a new DuckDB file at `path` */
+  async persistDatabase(path: string): Promise<void> {
+    return invoke('persist_database', { path });
+  }
+
+  async getCurrentDatabase(): Promise<CurrentDatabase | null> {
+    return invoke<CurrentDatabase | null>('get_current_database');
+  }
+
+  // Attached databases
+  async listDatabases(): Promise<AttachedDatabase[]> {
+    return invoke<AttachedDatabase[]>('list_databases');
+  }
+
+  async attachDatabase(
+    path: string,
+    alias?: string,
+    accessMode?: AccessMode
+  ): Promise<AttachedDatabase> {
+    return invoke<AttachedDatabase>('attach_database', { path, alias, accessMode });
+  }
+
+  async attachSource(
+    sourceType: SourceType,
+    location: string,
+    alias?: string,
+    accessMode?: AccessMode
+  ): Promise<AttachedDatabase> {
+    return invoke<AttachedDatabase>('attach_source', { sourceType, location, alias, accessMode });
+  }
+
+  async detachDatabase(name: string): Promise<void> {
+    return invoke('detach_database', { name });
+  }
+
+  async useDatabase(name: string, schema?: string): Promise<AttachedDatabase> {
+    return invoke<AttachedDatabase>('use_database', { name, schema });
   }
 
   // Table operations
-  async getTables(): Promise<TableInfo[]> {
-    return invoke<TableInfo[]>('get_tables');
+  /** Row counts are estimates unless `exactCounts` is set; sizes are only looked up with `storageSizes` */
+  async getTables(exactCounts?: boolean, storageSizes?: boolean): Promise<TableInfo[]> {
+    return invoke<TableInfo[]>('get_tables', { exactCounts, storageSizes });
   }
 
-  async getTableSchema(tableName: string): Promise<TableSchema> {
-    return invoke<TableSchema>('get_table_schema', { tableName });
+  /** Databases → schemas → tables, views, sequences, macros and indexes */
+  async getCatalog(): Promise<CatalogDatabase[]> {
+    return invoke<CatalogDatabase[]>('get_catalog');
+  }
+
+  async getTableSchema(
+    tableName: string,
+    database?: string,
+    schema?: string
+  ): Promise<TableSchema> {
+    return invoke<TableSchema>('get_table_schema', { tableName, database, schema });
+  }
+
+  /** CREATE statement and comments of an object; a table's include its indexes */
+  async getDdl(object: ObjectRef): Promise<string> {
+    return invoke<string>('get_ddl', { object });
+  }
+
+  /** Dependency-ordered script recreating every object of a database, without data */
+  async dumpSchema(database?: string): Promise<string> {
+    return invoke<string>('dump_schema', { database });
+  }
+
+  /** Column statistics of a table */
+  async profileTable(
+    tableName: string,
+    database?: string,
+    schema?: string,
+    options?: ProfileOptions
+  ): Promise<TableProfile> {
+    return invoke<TableProfile>('profile_table', { tableName, database, schema, options });
+  }
+
+  /** Column statistics of the result of `query` */
+  async profileQuery(query: string, options?: ProfileOptions): Promise<TableProfile> {
+    return invoke<TableProfile>('profile_table', { query, options });
   }
 
   async getTableData(
     tableName: string,
     limit: number,
-    offset: number
-  ): Promise<QueryResult> {
-    const result = await invoke<QueryResult>('get_table_data', { tableName, limit, offset });
+    offset: number,
+    options?: TableDataOptions,
+    database?: string,
+    schema?: string
+  ): Promise<TablePage> {
+    const result = await invoke<TablePage>('get_table_data', {
+      tableName,
+      database,
+      schema,
+      limit,
+      offset,
+      options,
+    });
     console.log('getTableData result:', JSON.stringify(result, null, 2));
     return result;
   }
 
   // Query operations
-  async executeQuery(sql: string): Promise<QueryResult> {
-    const result = await invoke<QueryResult>('execute_query', { sql });
+  async executeQuery(
+    sql: string,
+    queryId?: string,
+    timeoutMs?: number,
+    encoding?: ResultEncoding,
+    params?: QueryParams
+  ): Promise<QueryResult> {
+    const result = await invoke<QueryResult>('execute_query', {
+      sql,
+      queryId,
+      timeoutMs,
+      encoding,
+      params,
+    });
     console.log('executeQuery result:', JSON.stringify(result, null, 2));
     return result;
   }
 
+  async executeScript(
+    script: string,
+    queryId?: string,
+    timeoutMs?: number,
+    encoding?: ResultEncoding
+  ): Promise<ScriptResult> {
+    return invoke<ScriptResult>('execute_script', { script, queryId, timeoutMs, encoding });
+  }
+
+  async cancelQuery(queryId: string): Promise<boolean> {
+    return invoke<boolean>('cancel_query', { queryId });
+  }
+
+  // Blob operations
+  async getCellBlob(
+    tableName: string,
+    rowid: number,
+    column: string,
+    database?: string,
+    schema?: string
+  ): Promise<BlobData> {
+    return invoke<BlobData>('get_cell_blob', { tableName, database, schema, rowid, column });
+  }
+
+  async saveBlob(
+    tableName: string,
+    rowid: number,
+    column: string,
+    filePath: string,
+    database?: string,
+    schema?: string
+  ): Promise<void> {
+    return invoke('save_blob', { tableName, database, schema, rowid, column, filePath });
+  }
+
+  // Cursor operations
+  async openQuery(sql: string, encoding?: ResultEncoding): Promise<CursorInfo> {
+    return invoke<CursorInfo>('open_query', { sql, encoding });
+  }
+
+  async fetchRows(cursorId: string, maxRows: number): Promise<RowBatch> {
+    return invoke<RowBatch>('fetch_rows', { cursorId, maxRows });
+  }
+
+  async closeCursor(cursorId: string): Promise<boolean> {
+    return invoke<boolean>('close_cursor', { cursorId });
+  }
+
   // Export operations
   async exportData(
     format: ExportFormat,
@@ -77,20 +239,42 @@ export class TauriService {
   }
 
   // Import operations
-  async importCsv(filePath: string, tableName: string): Promise<void> {
-    return invoke('import_csv', { filePath, tableName });
+  async importCsv(
+    filePath: string,
+    tableName: string,
+    database?: string,
+    schema?: string
+  ): Promise<void> {
+    return invoke('import_csv', { filePath, tableName, database, schema });
   }
 
-  async importParquet(filePath: string, tableName: string): Promise<void> {
-    return invoke('import_parquet', { filePath, tableName });
+  async importParquet(
+    filePath: string,
+    tableName: string,
+    database?: string,
+    schema?: string
+  ): Promise<void> {
+    return invoke('import_parquet', { filePath, tableName, database, schema });
   }
 
   async importExcel(
     filePath: string,
     tableName: string,
-    sheetName?: string
+    sheetName?: string,
+    database?: string,
+    schema?: string
   ): Promise<void> {
-    return invoke('import_excel', { filePath, tableName, sheetName });
+    return invoke('import_excel', { filePath, tableName, sheetName, database, schema });
+  }
+
+  /** Create a view over files matching `paths` (globs allowed); temporary unless `temporary` is false */
+  async registerFileView(
+    name: string,
+    paths: string[],
+    format: FileViewFormat,
+    temporary?: boolean
+  ): Promise<void> {
+    return invoke('register_file_view', { name, paths, format, temporary });
   }
 
   // Sample data operations
@@ -98,6 +282,15 @@ export class TauriService {
     return invoke('create_sample_data', { sampleType });
   }
 
+  // Settings operations
+  async getSettings(): Promise<Settings> {
+    return invoke<Settings>('get_settings');
+  }
+
+  async updateSettings(newSettings: Settings): Promise<void> {
+    return invoke('update_settings', { newSettings });
+  }
+
   // Dialog operations
   async openFileDialog(filters?: { name: string; extensions: string[] }[]): Promise<string | null> {
     const result = await open({
@@ -0,0 +1,12 @@
+import { AppError } from '../models/database.model';
+
+/** Whether `e` is an error rejected by a backend command */
+export function isAppError(e: unknown): e is AppError {
+  return typeof e === 'object' && e !== null && 'kind' in e && 'message' in e;
+}
+
+/** Human-readable message for anything thrown by a command or the frontend */
+export function errorMessage(e: unknown): string {
+  if (isAppError(e)) return e.message;
+  return e instanceof Error ? e.message : String(e);
+}
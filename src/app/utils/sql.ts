@@ -0,0 +1,9 @@
+/** Quote a table or column name for SQL, doubling embedded double quotes */
+export function quoteIdentifier(name: string): string {
+  return `"${name.replace(/"/g, '""')}"`;
+}
+
+/** Fully qualified, quoted name of a table: "database"."schema"."table" */
+export function qualifiedTableName(table: { database: string; schema: string; name: string }): string {
+  return [table.database, table.schema, table.name].map(quoteIdentifier).join('.');
+}